@@ -1,5 +1,6 @@
-use core::time;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use sdl2::{
     event::Event,
@@ -13,19 +14,48 @@ use sdl2::{
 };
 
 use crate::{
-    enemy::Enemy,
-    entity::{Entity, EntityBase, EntityEvent},
-    player::Player,
-    texture::{
-        ComponentTexture, BASE_TEXTURES, ENGINE_EFFECTS_IDLE_TEXTURES,
-        ENGINE_EFFECTS_POWERING_TEXTURES, ENGINE_TEXTURES, PROJECTILE_TEXTURES, WEAPON_TEXTURES,
-    },
+    boss::{Boss, BossKind},
+    clock::Clock,
+    collision::{Contact, Layer},
+    enemy::{Enemy, EnemyKind},
+    engine::EngineKind,
+    entity::{Entity, EntityEvent, UpdateContext},
+    grid::Grid,
+    highscore::{Conditions, Entry, HighScores},
+    hud::Hud,
+    level::{Script, Spawn},
+    options::{Difficulty, Options},
+    player::{Loadout, Player},
+    replay::{Header, Recorder, Replay},
+    score::Score,
+    shield::ShieldKind,
+    state::{cycle, Results, State, LOADOUT_ROWS},
+    texture::{TextureCache, RENDER_DEBUG},
+    weapon::WeaponKind,
 };
 
 pub struct App {
     sdl: Sdl,
     canvas: WindowCanvas,
-    is_game_over: bool,
+    options: Options,
+    seed: u64,
+    rng: StdRng,
+    clock: Clock,
+    next_enemy_frame: u64,
+    // a boss shows up once a stage has run this long
+    next_boss_frame: u64,
+    // scripted waves, random ones take over once it runs out
+    script: Option<Script>,
+    // broad-phase for collisions, rebuilt every tick
+    grid: Grid,
+    score: Score,
+    high_scores: HighScores,
+    state: State,
+    // picked on the loadout screen, kept for the next run
+    loadout: Loadout,
+    difficulty: Difficulty,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
 }
 
 type EntityType<'a> = Box<dyn Entity<'a> + 'a>;
@@ -33,111 +63,373 @@ type EntityType<'a> = Box<dyn Entity<'a> + 'a>;
 impl App {
     const WIDTH: u32 = 1280;
     const HEIGHT: u32 = 720;
-    pub fn new(name: &str) -> Result<Self, String> {
+    const TICK: Duration = Duration::from_nanos(1_000_000_000 / Clock::TICK_RATE);
+    // longest frame time we try to catch up on, so a stall doesn't snowball
+    const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+    // milliseconds of regular waves before each boss
+    const STAGE_LENGTH: u64 = 60_000;
+    // about the size of the largest ships
+    const GRID_CELL_SIZE: i32 = 128;
+    // time the game over screen stays up before the results
+    const GAME_OVER_TIME: u64 = 2_000;
+    const MAX_NAME_LENGTH: usize = 12;
+    pub fn new(name: &str, options: Options) -> Result<Self, String> {
+        if options.is_headless() {
+            // the dummy driver needs neither a display nor a GPU, textures
+            // are still loaded through a software renderer
+            std::env::set_var("SDL_VIDEODRIVER", "dummy");
+        }
         let sdl = sdl2::init().unwrap();
         let _image_context = sdl2::image::init(InitFlag::PNG | InitFlag::JPG).unwrap();
-        let window = sdl
-            .video()?
-            .window(name, Self::WIDTH, Self::HEIGHT)
-            .position_centered()
-            .build()
-            .map_err(|e| e.to_string())?;
-        let mut canvas = window
-            .into_canvas()
-            .accelerated()
-            .build()
-            .map_err(|e| e.to_string())?;
+        let video = sdl.video()?;
+        let mut window = video.window(name, Self::WIDTH, Self::HEIGHT);
+        if options.is_headless() {
+            window.hidden();
+        } else {
+            window.position_centered();
+        }
+        let window = window.build().map_err(|e| e.to_string())?;
+        let canvas = window.into_canvas();
+        let canvas = if options.is_headless() {
+            canvas.software()
+        } else {
+            canvas.accelerated().present_vsync()
+        };
+        let mut canvas = canvas.build().map_err(|e| e.to_string())?;
         canvas.set_draw_color(Color::RGBA(96, 128, 255, 255));
         canvas.clear();
+        let replay = match &options.replay {
+            Some(path) => Some(Replay::load(path)?),
+            None => None,
+        };
+        // a replay plays the level it was recorded on
+        let level = match &replay {
+            Some(replay) => replay.header.level.as_ref(),
+            None => options.level.as_ref(),
+        };
+        let script = match level {
+            Some(path) => Some(Script::load(path)?),
+            None => None,
+        };
+        if let Some(script) = &script {
+            println!("level: {}", script.level.name);
+        }
+        let high_scores = HighScores::load(&options.scores);
+        let loadout = Loadout {
+            engine: options.engine,
+            ..Loadout::default()
+        };
+        let difficulty = options.difficulty;
+        // the rest is set up by `start_run`
         Ok(Self {
             sdl,
             canvas,
-            is_game_over: false,
+            options,
+            seed: 0,
+            rng: StdRng::seed_from_u64(0),
+            clock: Clock::default(),
+            next_enemy_frame: 0,
+            next_boss_frame: 0,
+            script,
+            grid: Grid::new(Self::GRID_CELL_SIZE),
+            score: Score::default(),
+            high_scores,
+            state: State::Title,
+            loadout,
+            difficulty,
+            recorder: None,
+            replay,
         })
     }
 
-    fn make_player(texture_creator: &TextureCreator<WindowContext>) -> Player {
-        let weapon_texture = ComponentTexture::new(&texture_creator, &WEAPON_TEXTURES[0]);
-        let engine_base_texture = ComponentTexture::new(&texture_creator, &ENGINE_TEXTURES[0]);
-        let idle_texture =
-            ComponentTexture::new(&texture_creator, &ENGINE_EFFECTS_IDLE_TEXTURES[0]);
-        let engine_texture =
-            ComponentTexture::new(&texture_creator, &ENGINE_EFFECTS_POWERING_TEXTURES[0]);
-        let projectile_texture = ComponentTexture::new(&texture_creator, &PROJECTILE_TEXTURES[0]);
-        let body_texture = ComponentTexture::new(&texture_creator, &BASE_TEXTURES[1]);
+    fn make_player<'a>(&self, texture_creator: &'a TextureCreator<WindowContext>) -> Player<'a> {
         let game_viewport = Rect::new(0, 0, Self::WIDTH, Self::HEIGHT);
-        let player = Player::new(
+        Player::new(
+            texture_creator,
             game_viewport,
-            engine_texture,
-            engine_base_texture,
-            idle_texture,
-            weapon_texture,
-            body_texture,
-            projectile_texture,
-        );
-        player
+            self.loadout,
+            self.difficulty.lives(),
+        )
     }
 
-    pub fn run<'a>(&mut self) -> Result<(), String> {
+    pub fn run(&mut self) -> Result<(), String> {
         let texture_creator = self.canvas.texture_creator();
-        let player = Self::make_player(&texture_creator);
-        let mut entities = vec![];
-        let player: Box<dyn Entity> = Box::new(player);
-        entities.push(Some(player));
+        let textures = TextureCache::new(&texture_creator);
 
-        // make frame rate more accurate
-        let mut ticks = unsafe { sdl2_sys::SDL_GetTicks64() };
-        let mut time_out = None;
-        let mut remainder = 0.0;
+        if let Some(ticks) = self.options.headless_ticks {
+            let mut entities = self.start_run(&texture_creator)?;
+            self.run_headless(ticks, &mut entities, &textures);
+            self.finish_recording();
+            return Ok(());
+        }
 
-        let mut enemy_spawn_time = rand::random::<u32>() % 60;
+        let ttf = sdl2::ttf::init().map_err(|e| e.to_string())?;
+        let game_viewport = Rect::new(0, 0, Self::WIDTH, Self::HEIGHT);
+        let hud = Hud::new(&ttf, &texture_creator, game_viewport)?;
+
+        let mut entities = vec![];
+        // a replay goes straight to the recorded run
+        if self.replay.is_some() {
+            entities = self.start_run(&texture_creator)?;
+            self.state = State::Playing;
+        }
+        let mut previous = Instant::now();
+        let mut lag = Duration::ZERO;
 
         'mainloop: loop {
+            let mut frame_advance = false;
             for event in self.sdl.event_pump()?.poll_iter() {
-                match event {
+                if let Event::Quit { .. } = event {
+                    break 'mainloop;
+                }
+                let keycode = match event {
                     Event::KeyDown {
-                        keycode: Some(Keycode::Escape),
+                        keycode: Some(keycode),
+                        repeat: false,
                         ..
+                    } => Some(keycode),
+                    _ => None,
+                };
+                match self.state {
+                    State::Title => match keycode {
+                        Some(Keycode::Return | Keycode::Space) => {
+                            self.state = State::Loadout { row: 0 };
+                        }
+                        Some(Keycode::Escape) => break 'mainloop,
+                        _ => {}
+                    },
+                    State::Loadout { .. } => {
+                        if self.handle_loadout_event(keycode) {
+                            entities = self.start_run(&texture_creator)?;
+                            self.state = State::Playing;
+                            lag = Duration::ZERO;
+                        }
+                    }
+                    State::Playing | State::Paused | State::GameOver { .. }
+                        if keycode == Some(Keycode::Escape) =>
+                    {
+                        // a replay has no title to go back to
+                        if self.replay.is_some() {
+                            break 'mainloop;
+                        }
+                        self.end_run(&mut entities);
+                    }
+                    State::Playing if keycode == Some(Keycode::P) => self.state = State::Paused,
+                    State::Paused => match keycode {
+                        Some(Keycode::P) => {
+                            self.state = State::Playing;
+                            // step right away so rendering picks up where the
+                            // paused frame left off
+                            lag = Self::TICK;
+                        }
+                        Some(Keycode::Period) => frame_advance = true,
+                        // keys released while paused still have to reach the
+                        // ship, or it keeps moving once play resumes
+                        _ => {
+                            if self.replay.is_none() {
+                                self.handle_event(&mut entities, event);
+                            }
+                        }
+                    },
+                    State::Playing | State::GameOver { .. } => {
+                        // inputs come from the replay file during playback
+                        if self.replay.is_none() {
+                            self.handle_event(&mut entities, event);
+                        }
+                    }
+                    State::Results(_) => {
+                        if self.handle_results_event(event) {
+                            self.end_run(&mut entities);
+                        }
                     }
-                    | Event::Quit { .. } => break 'mainloop,
-                    _ => self.handle_event(&mut entities, event),
                 }
             }
-            let entity_events = self.update(&mut entities);
-            for events in entity_events {
-                match events {
-                    EntityEvent::SpawnBullet(bullet) => entities.push(Some(Box::new(bullet))),
-                    EntityEvent::Empty => {}
+
+            let now = Instant::now();
+            let elapsed = (now - previous).min(Self::MAX_FRAME_TIME);
+            previous = now;
+            let mut steps = 0;
+            match self.state {
+                State::Paused if frame_advance => steps = 1,
+                State::Playing | State::GameOver { .. } => {
+                    lag += elapsed;
+                    while lag >= Self::TICK {
+                        lag -= Self::TICK;
+                        steps += 1;
+                    }
                 }
+                _ => {}
             }
+            for _ in 0..steps {
+                let is_ship_down = self.step(&mut entities, &textures);
 
-            self.handle_collision(&mut entities);
+                if self.is_replay_finished() {
+                    break 'mainloop;
+                }
 
-            self.clear(&mut entities);
+                match self.state {
+                    // frame advance can take the ship down while paused too
+                    State::Playing | State::Paused if is_ship_down => {
+                        self.state = State::GameOver {
+                            until: self.clock.frame()
+                                + Clock::ticks_from_millis(Self::GAME_OVER_TIME),
+                        };
+                    }
+                    State::GameOver { until } if self.clock.frame() >= until => {
+                        // a replay has nothing new to enter
+                        if self.replay.is_some() {
+                            break 'mainloop;
+                        }
+                        self.show_results();
+                        break;
+                    }
+                    _ => {}
+                }
+            }
 
             self.canvas.clear();
-            self.render(&mut entities);
-            if self.is_game_over {
-                self.game_over_screen();
+            match self.state {
+                State::Title => hud.render_title(&mut self.canvas),
+                State::Loadout { row } => {
+                    let values = self.loadout_values();
+                    hud.render_loadout(&mut self.canvas, &values, row);
+                }
+                _ => {
+                    // while paused, show the exact state of the current tick
+                    let alpha = match self.state {
+                        State::Playing | State::GameOver { .. } => {
+                            lag.as_secs_f64() / Self::TICK.as_secs_f64()
+                        }
+                        _ => 1.0,
+                    };
+                    self.render(&mut entities, alpha);
+                    let status = entities.iter().flatten().find_map(|entity| entity.status());
+                    hud.render(&mut self.canvas, &self.score, &self.clock, status.as_ref());
+                    match self.state {
+                        State::Paused => self.pause_screen(),
+                        State::GameOver { .. } => self.game_over_screen(),
+                        State::Results(_) => {
+                            self.game_over_screen();
+                            self.render_results(&hud);
+                        }
+                        _ => {}
+                    }
+                }
             }
             self.canvas.present();
+        }
+        self.finish_recording();
+        Ok(())
+    }
 
-            Self::spawn_enemy(&mut enemy_spawn_time, &mut entities, &texture_creator);
+    // reset the world for a new run, returns its entities with the player's
+    // ship in it
+    fn start_run<'a>(
+        &mut self,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Result<Vec<Option<EntityType<'a>>>, String> {
+        // a replay only reproduces the run with the settings it was recorded
+        // with
+        match &self.replay {
+            Some(replay) => {
+                self.seed = replay.header.seed;
+                self.loadout = replay.header.loadout;
+                self.difficulty = replay.header.difficulty;
+            }
+            None => self.seed = self.options.seed.unwrap_or_else(rand::random),
+        }
+        println!("seed: {}", self.seed);
+        // only the first run gets recorded
+        if let Some(path) = self.options.record.take() {
+            let header = Header {
+                seed: self.seed,
+                loadout: self.loadout,
+                difficulty: self.difficulty,
+                level: self.script.as_ref().map(|script| script.path.clone()),
+            };
+            self.recorder = Some(Recorder::create(&path, &header)?);
+        }
+        if let Some(script) = &mut self.script {
+            script.restart();
+        }
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.clock = Clock::default();
+        self.next_enemy_frame = self.rng.gen_range(0..60);
+        self.next_boss_frame = Clock::ticks_from_millis(Self::STAGE_LENGTH);
+        self.score = Score::default();
+        let player: EntityType = Box::new(self.make_player(texture_creator));
+        Ok(vec![Some(player)])
+    }
 
-            Self::cap_frame_rate(&mut ticks, &mut remainder);
+    // drop the current run and go back to the title
+    fn end_run(&mut self, entities: &mut Vec<Option<EntityType>>) {
+        self.finish_recording();
+        self.recorder = None;
+        entities.clear();
+        self.state = State::Title;
+    }
 
-            if let Some(time_out) = time_out {
-                if ticks >= time_out {
-                    break;
-                }
-            }
+    // run the simulation as fast as possible without presenting anything,
+    // stopping early once the player is gone
+    fn run_headless<'a>(
+        &mut self,
+        ticks: u64,
+        entities: &mut Vec<Option<EntityType<'a>>>,
+        textures: &TextureCache<'a>,
+    ) {
+        let mut is_game_over = false;
+        while self.clock.frame() < ticks && !is_game_over && !self.is_replay_finished() {
+            is_game_over = self.step(entities, textures);
+        }
+        println!(
+            "headless: seed {}, ran {} ticks, {} entities alive, score {}, game over: {}",
+            self.seed,
+            self.clock.frame(),
+            entities.len(),
+            self.score.points,
+            is_game_over
+        );
+    }
 
-            if self.is_game_over && time_out.is_none() {
-                // 2s game exit count down
-                time_out = Some(ticks + 2_000);
+    // advance the game world by one tick, returns true if the player's ship
+    // went down for good
+    fn step<'a>(
+        &mut self,
+        entities: &mut Vec<Option<EntityType<'a>>>,
+        textures: &TextureCache<'a>,
+    ) -> bool {
+        if let Some(replay) = &mut self.replay {
+            for event in replay.take_events(self.clock.frame()) {
+                self.handle_event(entities, event);
             }
         }
-        Ok(())
+
+        let entity_events = self.update(entities);
+        self.spawn(entities, entity_events);
+
+        let is_ship_down = self.handle_collision(entities);
+
+        self.clear(entities);
+
+        self.spawn_enemy(entities, textures);
+
+        self.score.update(&self.clock);
+
+        self.clock.advance();
+        is_ship_down
+    }
+
+    fn is_replay_finished(&self) -> bool {
+        match &self.replay {
+            Some(replay) => replay.is_finished(self.clock.frame()),
+            None => false,
+        }
+    }
+
+    fn finish_recording(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.finish(self.clock.frame());
+        }
     }
 
     fn game_over_screen(&mut self) {
@@ -148,106 +440,349 @@ impl App {
             .ok();
     }
 
+    fn conditions(&self) -> Conditions {
+        Conditions {
+            seed: self.seed,
+            level: self.script.as_ref().map(|script| script.level.name.clone()),
+            difficulty: self.difficulty,
+        }
+    }
+
+    // ask for a name if the score made the table, otherwise go straight to
+    // the leaderboard
+    fn show_results(&mut self) {
+        let results = if self
+            .high_scores
+            .qualifies(&self.conditions(), self.score.points)
+        {
+            if let Ok(video) = self.sdl.video() {
+                video.text_input().start();
+            }
+            Results::NameEntry(String::new())
+        } else {
+            Results::Leaderboard(None)
+        };
+        self.state = State::Results(results);
+    }
+
+    fn submit_score(&mut self, name: String) {
+        if let Ok(video) = self.sdl.video() {
+            video.text_input().stop();
+        }
+        let entry = Entry {
+            name,
+            score: self.score.points,
+        };
+        let rank = self.high_scores.insert(&self.conditions(), entry);
+        if let Err(e) = self.high_scores.save(&self.options.scores) {
+            eprintln!("{e}");
+        }
+        self.state = State::Results(Results::Leaderboard(rank));
+    }
+
+    // returns true once the player is done with the results
+    fn handle_results_event(&mut self, event: Event) -> bool {
+        let State::Results(results) = &mut self.state else {
+            return false;
+        };
+        match (results, event) {
+            (Results::NameEntry(name), Event::TextInput { text, .. }) => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if name.chars().count() < Self::MAX_NAME_LENGTH {
+                        name.push(c);
+                    }
+                }
+            }
+            (
+                Results::NameEntry(name),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                },
+            ) => {
+                name.pop();
+            }
+            (
+                Results::NameEntry(name),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    ..
+                },
+            ) => {
+                let name = match name.trim() {
+                    "" => "anonymous".to_string(),
+                    name => name.to_string(),
+                };
+                self.submit_score(name);
+            }
+            (
+                Results::Leaderboard(_),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return | Keycode::Escape),
+                    repeat: false,
+                    ..
+                },
+            ) => return true,
+            _ => {}
+        }
+        false
+    }
+
+    fn render_results(&mut self, hud: &Hud) {
+        let conditions = self.conditions();
+        match &self.state {
+            State::Results(Results::NameEntry(name)) => {
+                hud.render_name_entry(&mut self.canvas, self.score.points, name)
+            }
+            State::Results(Results::Leaderboard(rank)) => {
+                let entries = self.high_scores.entries(&conditions);
+                hud.render_leaderboard(&mut self.canvas, &conditions, entries, *rank);
+            }
+            _ => {}
+        }
+    }
+
+    // returns true once the player starts the run
+    fn handle_loadout_event(&mut self, keycode: Option<Keycode>) -> bool {
+        let State::Loadout { row } = &mut self.state else {
+            return false;
+        };
+        let rows = LOADOUT_ROWS.len();
+        let step = match keycode {
+            Some(Keycode::Up) => {
+                *row = (*row + rows - 1) % rows;
+                return false;
+            }
+            Some(Keycode::Down) => {
+                *row = (*row + 1) % rows;
+                return false;
+            }
+            Some(Keycode::Return | Keycode::Space) => return true,
+            Some(Keycode::Escape) => {
+                self.state = State::Title;
+                return false;
+            }
+            Some(Keycode::Left) => -1,
+            Some(Keycode::Right) => 1,
+            _ => return false,
+        };
+        let loadout = &mut self.loadout;
+        match *row {
+            0 => loadout.engine = cycle(&EngineKind::ALL, loadout.engine, step),
+            1 => loadout.weapon = cycle(&WeaponKind::ALL, loadout.weapon, step),
+            2 => loadout.shield = cycle(&ShieldKind::ALL, loadout.shield, step),
+            _ => self.difficulty = cycle(&Difficulty::ALL, self.difficulty, step),
+        }
+        false
+    }
+
+    // what's picked on each line of the loadout screen
+    fn loadout_values(&self) -> [&'static str; 4] {
+        [
+            self.loadout.engine.name(),
+            self.loadout.weapon.name(),
+            self.loadout.shield.name(),
+            self.difficulty.name(),
+        ]
+    }
+
+    fn pause_screen(&mut self) {
+        let color = self.canvas.draw_color();
+        self.canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(50, 50, 50, 120));
+        self.canvas
+            .fill_rect(Rect::new(0, 0, Self::WIDTH, Self::HEIGHT))
+            .ok();
+        // pause sign, two bars in the middle of the screen
+        let (center_x, center_y) = (Self::WIDTH as i32 / 2, Self::HEIGHT as i32 / 2);
+        self.canvas.set_draw_color(Color::RGBA(255, 255, 255, 200));
+        self.canvas
+            .fill_rects(&[
+                Rect::new(center_x - 30, center_y - 40, 20, 80),
+                Rect::new(center_x + 10, center_y - 40, 20, 80),
+            ])
+            .ok();
+        self.canvas.set_draw_color(color);
+    }
+
     fn spawn_enemy<'a>(
-        enemy_spawn_time: &mut u32,
+        &mut self,
         entities: &mut Vec<Option<Box<dyn Entity<'a> + 'a>>>,
-        texture_creator: &'a TextureCreator<WindowContext>,
+        textures: &TextureCache<'a>,
     ) {
-        *enemy_spawn_time -= 1;
-        if *enemy_spawn_time <= 0 {
-            let game_viewport = Rect::new(0, 0, Self::WIDTH, Self::HEIGHT);
-            let enemy = Enemy::new(texture_creator, game_viewport);
+        let game_viewport = Rect::new(0, 0, Self::WIDTH, Self::HEIGHT);
+        if let Some(script) = &mut self.script {
+            if !script.is_finished() {
+                for wave in script.take_waves(self.clock.millis()) {
+                    for spawn in &wave.enemies {
+                        let enemy =
+                            Enemy::new(textures, spawn, game_viewport, &self.clock, &mut self.rng);
+                        entities.push(Some(Box::new(enemy)));
+                    }
+                    if let Some(kind) = wave.boss {
+                        let boss = Boss::new(textures, kind, game_viewport, &self.clock);
+                        entities.push(Some(Box::new(boss)));
+                    }
+                }
+                // the first random stage starts when the level is through
+                self.next_boss_frame =
+                    self.clock.frame() + Clock::ticks_from_millis(Self::STAGE_LENGTH);
+                return;
+            }
+        }
+        // regular waves hold off while a boss is around, the next stage
+        // starts once it's gone
+        if entities.iter().flatten().any(|entity| entity.is_boss()) {
+            self.next_boss_frame =
+                self.clock.frame() + Clock::ticks_from_millis(Self::STAGE_LENGTH);
+            return;
+        }
+        if self.clock.frame() >= self.next_boss_frame {
+            let kind = BossKind::random(&mut self.rng);
+            let boss: Box<dyn Entity> =
+                Box::new(Boss::new(textures, kind, game_viewport, &self.clock));
+            entities.push(Some(boss));
+            return;
+        }
+        if self.clock.frame() >= self.next_enemy_frame {
+            let kind = EnemyKind::random(&mut self.rng);
+            let spawn = Spawn::random(kind);
+            let enemy = Enemy::new(textures, &spawn, game_viewport, &self.clock, &mut self.rng);
             let enemy: Box<dyn Entity> = Box::new(enemy);
             entities.push(Some(enemy));
 
-            *enemy_spawn_time = 30 + (rand::random::<u32>() % 60);
+            self.next_enemy_frame = self.clock.frame() + self.rng.gen_range(30..90);
         }
     }
 
-    fn cap_frame_rate(tick: &mut u64, remainder: &mut f64) {
-        let mut wait = 16.0 + *remainder;
-        *remainder = remainder.fract();
-        let frame_time = unsafe { sdl2_sys::SDL_GetTicks64() } - *tick;
-        wait -= (1f64).max(frame_time as f64);
-        std::thread::sleep(Duration::from_millis(wait as u64));
-        *remainder += 0.667;
-        *tick = unsafe { sdl2_sys::SDL_GetTicks64() };
-    }
-
     fn update<'a>(&mut self, entities: &mut Vec<Option<EntityType<'a>>>) -> Vec<EntityEvent<'a>> {
+        let player = entities
+            .iter()
+            .flatten()
+            .find(|entity| entity.layer() == Layer::PLAYER)
+            .and_then(|player| player.base())
+            .map(|base| base.center());
+        let context = UpdateContext {
+            clock: self.clock,
+            player,
+        };
         let mut events = vec![];
-        for entity in entities.iter_mut() {
-            if let Some(entity_inner) = entity {
-                events.push(entity_inner.update());
+        for entity in entities.iter_mut().flatten() {
+            if let Some(base) = entity.base_mut() {
+                base.save_position();
             }
+            events.extend(entity.update(&context));
         }
         events
     }
-    fn handle_collision<'a>(&mut self, entities: &mut Vec<Option<EntityType<'a>>>) {
-        // dummy version
-        let size = entities.len();
-        for i in 0..size {
-            for j in (i + 1)..size {
-                let (slice1, slice2) = entities.split_at_mut(j);
-                if let Some(ref mut e1) = slice1[i] {
-                    if let Some(ref mut e2) = slice2[0] {
-                        if !e1.valid() || !e2.valid() {
-                            continue;
-                        }
-                        if !e1.is_bullet() && e1.is_enemy() && e2.is_bullet() && e2.is_player()
-                            || e1.is_bullet() && e1.is_player() && !e2.is_bullet() && e2.is_enemy()
-                        {
-                            if let Some(e1_base) = e1.base_mut() {
-                                if let Some(e2_base) = e2.base_mut() {
-                                    if Self::is_collision(e1_base, e2_base) {
-                                        e1_base.valid = false;
-                                        e2_base.valid = false;
-                                    }
-                                }
-                            }
-                        }
-                        if !e1.is_bullet() && e1.is_player() && e2.is_bullet() && e2.is_enemy()
-                            || e1.is_bullet() && e1.is_enemy() && !e2.is_bullet() && e2.is_player()
-                            || !e1.is_bullet() && e1.is_player() && !e2.is_bullet() && e2.is_enemy()
-                            || !e2.is_bullet() && e2.is_player() && !e1.is_bullet() && e1.is_enemy()
-                        {
-                            if let Some(e1_base) = e1.base_mut() {
-                                if let Some(e2_base) = e2.base_mut() {
-                                    if Self::is_collision(e1_base, e2_base) {
-                                        e1_base.valid = false;
-                                        e2_base.valid = false;
-                                        self.is_game_over = true;
-                                    }
-                                }
-                            }
-                        }
+    // returns true if the player's ship went down for good
+    fn handle_collision<'a>(&mut self, entities: &mut Vec<Option<EntityType<'a>>>) -> bool {
+        let mut is_ship_down = false;
+        self.grid.clear();
+        for (index, entity) in entities.iter().enumerate() {
+            let Some(entity) = entity else { continue };
+            if entity.mask().is_empty() && entity.layer().is_empty() {
+                continue;
+            }
+            // cover the whole path since the last tick, so entities passing
+            // through each other still get paired up
+            if let Some(hitbox) = entity.hitbox() {
+                let (dx, dy) = Self::motion(entity);
+                self.grid
+                    .insert(index, hitbox.sweep((dx as f32, dy as f32)).bounds());
+            }
+        }
+        // only entities close enough to touch are paired up
+        for (i, j) in self.grid.pairs() {
+            let (slice1, slice2) = entities.split_at_mut(j);
+            if let Some(ref mut e1) = slice1[i] {
+                if let Some(ref mut e2) = slice2[0] {
+                    if !e1.valid() || !e2.valid() || e1.is_invincible() || e2.is_invincible() {
+                        continue;
                     }
+                    Self::resolve_collision(e1, e2);
+                    let is_down = |e: &EntityType| e.layer() == Layer::PLAYER && !e.valid();
+                    is_ship_down |= is_down(e1) || is_down(e2);
                 }
             }
         }
+        is_ship_down
     }
 
-    fn is_collision(e1_base: &EntityBase, e2_base: &EntityBase) -> bool {
-        let (e1_start_x, e2_start_x) = (e1_base.x, e2_base.x);
-        let (e1_end_x, e2_end_x) = (e1_base.width + e1_start_x, e2_base.width + e2_start_x);
-        let (e1_start_y, e2_start_y) = (e1_base.y, e2_base.y);
-        let (e1_end_y, e2_end_y) = (e1_base.height + e1_start_y, e2_base.height + e2_start_y);
-        if e1_start_x.max(e2_start_x) < e1_end_x.min(e2_end_x)
-            && e1_start_y.max(e2_start_y) < e1_end_y.min(e2_end_y)
-        {
-            true
-        } else {
-            false
+    // let two overlapping entities react to each other, each one only
+    // hears about the other if its layer is in its mask
+    fn resolve_collision<'a>(e1: &mut EntityType<'a>, e2: &mut EntityType<'a>) {
+        let e1_reacts = e1.mask().intersects(e2.layer());
+        let e2_reacts = e2.mask().intersects(e1.layer());
+        if !e1_reacts && !e2_reacts {
+            return;
+        }
+        if !Self::is_collision(e1, e2) {
+            return;
+        }
+        let (e1_center, e2_center) = match (e1.base(), e2.base()) {
+            (Some(e1_base), Some(e2_base)) => (e1_base.center(), e2_base.center()),
+            _ => return,
+        };
+        let e1_contact = Contact {
+            layer: e1.layer(),
+            damage: e1.damage(),
+            center: e1_center,
+        };
+        let e2_contact = Contact {
+            layer: e2.layer(),
+            damage: e2.damage(),
+            center: e2_center,
+        };
+        if e1_reacts {
+            e1.on_collision(&e2_contact);
+        }
+        if e2_reacts {
+            e2.on_collision(&e1_contact);
+        }
+    }
+
+    // checked again when resolving, an earlier hit may have moved either
+    // entity since the grid was built. `e1` is swept along its motion
+    // relative to `e2`, so a hit registers however far either one moved
+    // during the tick
+    fn is_collision<'a>(e1: &EntityType<'a>, e2: &EntityType<'a>) -> bool {
+        let (Some(e1_hitbox), Some(e2_hitbox)) = (e1.hitbox(), e2.hitbox()) else {
+            return false;
+        };
+        let ((dx1, dy1), (dx2, dy2)) = (Self::motion(e1), Self::motion(e2));
+        let motion = ((dx1 - dx2) as f32, (dy1 - dy2) as f32);
+        e1_hitbox.sweep(motion).overlaps(&e2_hitbox)
+    }
+
+    fn motion(entity: &EntityType) -> (i32, i32) {
+        entity.base().map_or((0, 0), |base| base.motion())
+    }
+
+    fn spawn<'a>(
+        &mut self,
+        entities: &mut Vec<Option<EntityType<'a>>>,
+        events: Vec<EntityEvent<'a>>,
+    ) {
+        for event in events {
+            match event {
+                EntityEvent::SpawnBullet(bullet) => entities.push(Some(Box::new(bullet))),
+                EntityEvent::SpawnExplosion(explosion) => entities.push(Some(Box::new(explosion))),
+                EntityEvent::Score(points) => self.score.kill(points, &self.clock),
+            }
         }
     }
 
     fn clear<'a>(&mut self, entities: &mut Vec<Option<EntityType<'a>>>) {
         let mut new_entities = vec![];
         let mut ind = vec![];
-        for (index, entity) in entities.iter().enumerate() {
+        let mut remains = vec![];
+        for (index, entity) in entities.iter_mut().enumerate() {
             if let Some(entity_inner) = entity {
                 if entity_inner.valid() {
                     ind.push(index);
+                } else {
+                    remains.extend(entity_inner.remains());
                 }
             }
         }
@@ -258,21 +793,35 @@ impl App {
         for entity in new_entities {
             entities.push(entity);
         }
+        self.spawn(entities, remains);
     }
 
-    fn render<'a>(&mut self, entities: &mut Vec<Option<Box<dyn Entity<'a> + 'a>>>) {
-        for entity in entities.iter_mut() {
-            if let Some(entity_inner) = entity {
-                entity_inner.render(&mut self.canvas);
+    fn render<'a>(&mut self, entities: &mut Vec<Option<Box<dyn Entity<'a> + 'a>>>, alpha: f64) {
+        for entity in entities.iter_mut().flatten() {
+            entity.render(&mut self.canvas, alpha);
+        }
+        if RENDER_DEBUG {
+            // hitboxes are where the last tick left them, without interpolation
+            let color = self.canvas.draw_color();
+            self.canvas.set_draw_color(Color::RGB(255, 0, 0));
+            for entity in entities.iter().flatten() {
+                if entity.layer().is_empty() {
+                    continue;
+                }
+                if let Some(hitbox) = entity.hitbox() {
+                    hitbox.render(&mut self.canvas);
+                }
             }
+            self.canvas.set_draw_color(color);
         }
     }
 
     fn handle_event<'a>(&mut self, entities: &mut Vec<Option<EntityType<'a>>>, event: Event) {
-        for entity in entities.iter_mut() {
-            if let Some(entity_inner) = entity {
-                entity_inner.handle_event(event.clone());
-            }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(self.clock.frame(), &event);
+        }
+        for entity in entities.iter_mut().flatten() {
+            entity.handle_event(event.clone());
         }
     }
 }
@@ -0,0 +1,370 @@
+use rand::Rng;
+use sdl2::{pixels::Color, rect::Rect, render::WindowCanvas};
+use serde::Deserialize;
+
+use crate::{
+    bullet::Bullet,
+    clock::Clock,
+    collision::{Contact, Hitbox, Layer, Shape},
+    emitter::{Emitter, Pattern},
+    enemy::{EnemyKind, ProjectileKind},
+    entity::{Entity, EntityBase, EntityEvent, UpdateContext},
+    explosion::Explosion,
+    movement::{Movement, Mover},
+    texture::{
+        ComponentTexture, TextureCache, ENEMY_BASE_TEXTURES, ENEMY_DESTRUCTION_TEXTURES,
+        ENEMY_ENGINE_TEXTURES, ENEMY_PROJECTILE_TEXTURES, ENEMY_WEAPON_TEXTURES,
+    },
+};
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+pub enum BossKind {
+    Battlecruiser,
+    Dreadnought,
+}
+
+// one stage of a boss fight, in effect until the next phase's threshold
+pub struct Phase {
+    // share of the hp pool left when this phase starts
+    pub threshold: f32,
+    pub movement: Movement,
+    pub pattern: Pattern,
+    // milliseconds between two volleys
+    pub fire_interval: u64,
+    pub bullet_speed: f32,
+}
+
+pub struct BossStats {
+    pub hp: i32,
+    pub speed: i32,
+    pub projectile: ProjectileKind,
+    pub points: u32,
+    // in order, starting with the one the boss enters with
+    pub phases: Vec<Phase>,
+}
+
+impl BossKind {
+    pub const ALL: [BossKind; 2] = [BossKind::Battlecruiser, BossKind::Dreadnought];
+
+    pub fn stats(&self) -> BossStats {
+        // bosses hold their ground once they're on screen
+        let hold = |column| Movement::StrafeHold {
+            column,
+            hold: u64::MAX,
+        };
+        match self {
+            BossKind::Battlecruiser => BossStats {
+                hp: 120,
+                speed: 2,
+                projectile: ProjectileKind::Wave,
+                points: 10_000,
+                phases: vec![
+                    Phase {
+                        threshold: 1.0,
+                        movement: hold(950),
+                        pattern: Pattern::Fan {
+                            count: 5,
+                            spread: 12.0,
+                        },
+                        fire_interval: 900,
+                        bullet_speed: 7.0,
+                    },
+                    Phase {
+                        threshold: 0.6,
+                        movement: hold(950),
+                        pattern: Pattern::Spiral {
+                            arms: 4,
+                            step: 12.0,
+                        },
+                        fire_interval: 250,
+                        bullet_speed: 6.0,
+                    },
+                    Phase {
+                        threshold: 0.3,
+                        movement: hold(750),
+                        pattern: Pattern::Radial { count: 20 },
+                        fire_interval: 700,
+                        bullet_speed: 6.0,
+                    },
+                ],
+            },
+            BossKind::Dreadnought => BossStats {
+                hp: 200,
+                speed: 1,
+                projectile: ProjectileKind::Ray,
+                points: 20_000,
+                phases: vec![
+                    Phase {
+                        threshold: 1.0,
+                        movement: hold(900),
+                        pattern: Pattern::Aimed {
+                            count: 3,
+                            spread: 10.0,
+                        },
+                        fire_interval: 800,
+                        bullet_speed: 12.0,
+                    },
+                    Phase {
+                        threshold: 0.66,
+                        movement: hold(900),
+                        pattern: Pattern::Spiral {
+                            arms: 6,
+                            step: 10.0,
+                        },
+                        fire_interval: 200,
+                        bullet_speed: 8.0,
+                    },
+                    Phase {
+                        threshold: 0.33,
+                        movement: hold(700),
+                        pattern: Pattern::Radial { count: 24 },
+                        fire_interval: 600,
+                        bullet_speed: 9.0,
+                    },
+                ],
+            },
+        }
+    }
+
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self::ALL[rng.gen_range(0..Self::ALL.len())]
+    }
+
+    // the regular ship class the boss borrows its sprites from
+    fn ship(&self) -> EnemyKind {
+        match self {
+            BossKind::Battlecruiser => EnemyKind::Battlecruiser,
+            BossKind::Dreadnought => EnemyKind::Dreadnought,
+        }
+    }
+}
+
+pub struct Boss<'a> {
+    base: EntityBase,
+    // built once, the phases are looked up every tick
+    stats: BossStats,
+    pub hp: i32,
+    phase: usize,
+    mover: Mover,
+    emitter: Emitter,
+    // simulated time of the last volley in milliseconds
+    last_fired: u64,
+    // ticks since the boss was shot down, while it breaks apart
+    dying: Option<u64>,
+    engine_texture: ComponentTexture<'a>,
+    body_texture: ComponentTexture<'a>,
+    hitbox: Hitbox,
+    weapon_texture: Option<ComponentTexture<'a>>,
+    projectile_texture: ComponentTexture<'a>,
+    projectile_hitbox: Hitbox,
+    destruction_texture: ComponentTexture<'a>,
+    // small blasts going off across the hull before the final explosion
+    blast_texture: ComponentTexture<'a>,
+}
+
+impl<'a> Entity<'a> for Boss<'a> {
+    fn update(&mut self, context: &UpdateContext) -> Vec<EntityEvent<'a>> {
+        self.engine_texture.tick();
+        self.body_texture.tick();
+        if let Some(weapon_texture) = &mut self.weapon_texture {
+            weapon_texture.tick();
+        }
+
+        if let Some(dying) = self.dying {
+            let dying = dying + 1;
+            self.dying = Some(dying);
+            if dying >= Self::DEATH_TICKS {
+                self.base.valid = false;
+            }
+            if dying % Self::BLAST_INTERVAL == 0 {
+                return vec![EntityEvent::SpawnExplosion(self.blast(dying))];
+            }
+            return vec![];
+        }
+
+        self.mover.update(&mut self.base, context);
+        let clock = &context.clock;
+        let fire_interval = self.stats.phases[self.phase].fire_interval;
+        if clock.millis() - self.last_fired >= fire_interval {
+            self.last_fired = clock.millis();
+            return self
+                .fire(context.player)
+                .into_iter()
+                .map(EntityEvent::SpawnBullet)
+                .collect();
+        }
+        vec![]
+    }
+
+    fn render(&mut self, canvas: &mut WindowCanvas, alpha: f64) {
+        let position = self.base.render_position(alpha);
+        self.engine_texture
+            .render(position, Self::DEFAULT_ANGLE, canvas);
+        self.body_texture
+            .render(position, Self::DEFAULT_ANGLE, canvas);
+        if let Some(weapon_texture) = &mut self.weapon_texture {
+            weapon_texture.render(position, Self::DEFAULT_ANGLE, canvas);
+        }
+        self.render_health_bar(canvas);
+    }
+
+    fn valid(&self) -> bool {
+        self.base.valid
+    }
+
+    fn hitbox(&self) -> Option<Shape> {
+        Some(self.hitbox.place(&self.base, Self::DEFAULT_ANGLE))
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::ENEMY
+    }
+    fn mask(&self) -> Layer {
+        Layer::PLAYER | Layer::PLAYER_PROJECTILE
+    }
+
+    fn is_boss(&self) -> bool {
+        true
+    }
+
+    // nothing more to hit while it breaks apart
+    fn is_invincible(&self) -> bool {
+        self.dying.is_some()
+    }
+
+    fn damage(&self) -> i32 {
+        Self::RAM_DAMAGE
+    }
+
+    fn on_collision(&mut self, other: &Contact) {
+        self.hp = (self.hp - other.damage).max(0);
+        if self.hp == 0 {
+            self.dying = Some(0);
+            return;
+        }
+        let share = self.hp as f32 / self.stats.hp as f32;
+        let phase = self
+            .stats
+            .phases
+            .iter()
+            .rposition(|phase| share <= phase.threshold)
+            .unwrap_or(0);
+        if phase > self.phase {
+            self.enter_phase(phase);
+        }
+    }
+
+    fn remains(&mut self) -> Vec<EntityEvent<'a>> {
+        if self.dying.is_none() {
+            return vec![];
+        }
+        vec![
+            EntityEvent::SpawnExplosion(Explosion::new(
+                &self.base,
+                Self::DEFAULT_ANGLE,
+                self.destruction_texture.clone(),
+            )),
+            EntityEvent::Score(self.stats.points),
+        ]
+    }
+
+    fn base(&self) -> Option<&EntityBase> {
+        Some(&self.base)
+    }
+    fn base_mut(&mut self) -> Option<&mut EntityBase> {
+        Some(&mut self.base)
+    }
+}
+
+impl<'a> Boss<'a> {
+    const DEFAULT_ANGLE: f64 = -90.0;
+    const FACING: f32 = 180.0;
+    const RAM_DAMAGE: i32 = 3;
+    const DEATH_TICKS: u64 = 90;
+    const BLAST_INTERVAL: u64 = 10;
+    const HEALTH_BAR_SIZE: (u32, u32) = (600, 12);
+    const HEALTH_BAR_MARGIN: i32 = 20;
+
+    pub fn new(textures: &TextureCache<'a>, kind: BossKind, viewport: Rect, clock: &Clock) -> Self {
+        let stats = kind.stats();
+        let index = kind.ship().index();
+        let body_texture = textures.get(&ENEMY_BASE_TEXTURES[index]);
+        let (_, height) = body_texture.size();
+        let (x, y) = (
+            viewport.width() as i32,
+            (viewport.height() as i32 - height as i32) / 2,
+        );
+        let base = EntityBase::new(x, y, body_texture.size(), -stats.speed, 0, viewport);
+        let phase = &stats.phases[0];
+        let projectile_info = &ENEMY_PROJECTILE_TEXTURES[stats.projectile.index()];
+        Self {
+            base,
+            hp: stats.hp,
+            phase: 0,
+            mover: Mover::new(phase.movement.clone(), (x, y), stats.speed),
+            emitter: Emitter::new(phase.pattern, phase.bullet_speed),
+            stats,
+            last_fired: clock.millis(),
+            dying: None,
+            engine_texture: textures.get(&ENEMY_ENGINE_TEXTURES[index]),
+            body_texture,
+            hitbox: textures.hitbox(&ENEMY_BASE_TEXTURES[index], Hitbox::hull),
+            weapon_texture: ENEMY_WEAPON_TEXTURES[index]
+                .as_ref()
+                .map(|info| textures.get(info)),
+            projectile_texture: textures.get(projectile_info),
+            projectile_hitbox: textures.hitbox(projectile_info, Hitbox::bounding),
+            destruction_texture: textures.get(&ENEMY_DESTRUCTION_TEXTURES[index]),
+            blast_texture: textures.get(&ENEMY_DESTRUCTION_TEXTURES[EnemyKind::Fighter.index()]),
+        }
+    }
+
+    fn enter_phase(&mut self, phase: usize) {
+        let next = &self.stats.phases[phase];
+        self.phase = phase;
+        // the new pattern picks up from wherever the boss is now
+        self.mover = Mover::new(
+            next.movement.clone(),
+            (self.base.x, self.base.y),
+            self.stats.speed,
+        );
+        self.emitter = Emitter::new(next.pattern, next.bullet_speed);
+    }
+
+    fn fire(&mut self, target: Option<(i32, i32)>) -> Vec<Bullet<'a>> {
+        self.emitter.volley(
+            &self.base,
+            Self::FACING,
+            target,
+            self.stats.projectile.damage(),
+            &self.projectile_hitbox,
+            &self.projectile_texture,
+        )
+    }
+
+    // a small blast somewhere on the hull, scattered by `tick` so the
+    // sequence is the same every time
+    fn blast(&self, tick: u64) -> Explosion<'a> {
+        let (width, height) = self.blast_texture.size();
+        let x = self.base.x + (tick as i32 * 37) % self.base.width - width as i32 / 2;
+        let y = self.base.y + (tick as i32 * 53) % self.base.height - height as i32 / 2;
+        let base = EntityBase::new(x, y, (width, height), 0, 0, self.base.viewport);
+        Explosion::new(&base, Self::DEFAULT_ANGLE, self.blast_texture.clone())
+    }
+
+    fn render_health_bar(&self, canvas: &mut WindowCanvas) {
+        let (width, height) = Self::HEALTH_BAR_SIZE;
+        let x = self.base.viewport.x + (self.base.viewport.w - width as i32) / 2;
+        let y = self.base.viewport.y + Self::HEALTH_BAR_MARGIN;
+        let filled = width * self.hp.max(0) as u32 / self.stats.hp as u32;
+
+        let color = canvas.draw_color();
+        canvas.set_draw_color(Color::RGB(60, 60, 60));
+        canvas.fill_rect(Rect::new(x, y, width, height)).ok();
+        if filled > 0 {
+            canvas.set_draw_color(Color::RGB(200, 40, 40));
+            canvas.fill_rect(Rect::new(x, y, filled, height)).ok();
+        }
+        canvas.set_draw_color(color);
+    }
+}
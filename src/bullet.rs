@@ -1,61 +1,97 @@
 use crate::{
-    entity::{Entity, EntityBase, EntityEvent},
+    collision::{Hitbox, Layer, Shape},
+    entity::{Entity, EntityBase, EntityEvent, UpdateContext},
     texture::ComponentTexture,
 };
 
 pub enum Side {
     Enemy,
-    Player
+    Player,
 }
 
 pub struct Bullet<'a> {
     base: EntityBase,
     side: Side,
+    // exact position and velocity in pixels per tick, `base` holds the
+    // position rounded to whole pixels
+    position: (f32, f32),
+    velocity: (f32, f32),
     angle: f64,
+    damage: i32,
+    hitbox: Hitbox,
     texture: ComponentTexture<'a>,
 }
 
 impl<'a> Bullet<'a> {
-    pub fn new(base: EntityBase, side: Side, angle: f64, texture: ComponentTexture<'a>) -> Self {
+    pub fn new(
+        base: EntityBase,
+        side: Side,
+        velocity: (f32, f32),
+        damage: i32,
+        hitbox: Hitbox,
+        texture: ComponentTexture<'a>,
+    ) -> Self {
+        // projectile sprites point up, turn them to face where they're heading
+        let angle = (velocity.1 as f64).atan2(velocity.0 as f64).to_degrees() + 90.0;
         Self {
+            position: (base.x as f32, base.y as f32),
             base,
             side,
+            velocity,
             texture,
             angle,
+            damage,
+            hitbox,
         }
     }
 }
 
 impl<'a> Entity<'a> for Bullet<'a> {
-    fn update(&mut self) -> EntityEvent<'a> {
-        if !self.base.update_x() {
+    fn update(&mut self, _context: &UpdateContext) -> Vec<EntityEvent<'a>> {
+        self.position.0 += self.velocity.0;
+        self.position.1 += self.velocity.1;
+        let (x, y) = (
+            self.position.0.round() as i32,
+            self.position.1.round() as i32,
+        );
+        self.base.dx = x - self.base.x;
+        self.base.dy = y - self.base.y;
+        self.base.x = x;
+        self.base.y = y;
+        if !self.base.in_viewport() {
             self.base.valid = false;
         }
-        EntityEvent::Empty
+        self.texture.tick();
+        vec![]
     }
 
-    fn render(&mut self, canvas: &mut sdl2::render::WindowCanvas) {
+    fn render(&mut self, canvas: &mut sdl2::render::WindowCanvas, alpha: f64) {
         self.texture
-            .render((self.base.x, self.base.y), self.angle, canvas);
+            .render(self.base.render_position(alpha), self.angle, canvas);
     }
 
     fn valid(&self) -> bool {
         self.base.valid
     }
 
-    fn is_bullet(&self) -> bool {
-        true
+    fn damage(&self) -> i32 {
+        self.damage
     }
-    fn is_enemy(&self) -> bool {
+
+    fn hitbox(&self) -> Option<Shape> {
+        Some(self.hitbox.place(&self.base, self.angle))
+    }
+
+    fn layer(&self) -> Layer {
         match self.side {
-            Side::Enemy => true,
-            Side::Player => false,
+            Side::Enemy => Layer::ENEMY_PROJECTILE,
+            Side::Player => Layer::PLAYER_PROJECTILE,
         }
     }
-    fn is_player(&self) -> bool {
+    fn mask(&self) -> Layer {
         match self.side {
-            Side::Enemy => false,
-            Side::Player => true,
+            Side::Enemy => Layer::PLAYER,
+            Side::Player => Layer::ENEMY,
         }
     }
     fn base(&self) -> Option<&EntityBase> {
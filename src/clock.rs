@@ -0,0 +1,30 @@
+// Simulated game time. It only moves when the simulation is stepped, so
+// everything reading it stops while the game is paused and stays in sync
+// with movement, which is expressed per tick.
+#[derive(Clone, Copy, Default)]
+pub struct Clock {
+    frame: u64,
+}
+
+impl Clock {
+    // simulation steps per second
+    pub const TICK_RATE: u64 = 60;
+
+    // number of ticks simulated so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    // simulated time in milliseconds
+    pub fn millis(&self) -> u64 {
+        self.frame * 1000 / Self::TICK_RATE
+    }
+
+    pub fn ticks_from_millis(millis: u64) -> u64 {
+        millis * Self::TICK_RATE / 1000
+    }
+
+    pub fn advance(&mut self) {
+        self.frame += 1;
+    }
+}
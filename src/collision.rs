@@ -0,0 +1,389 @@
+use std::{ops::BitOr, rc::Rc};
+
+use sdl2::{rect::Point, render::WindowCanvas};
+
+use crate::{entity::EntityBase, grid::Bounds, texture::SCALE};
+
+// Collision layers, a set of bits. Every entity sits on a layer and has a
+// mask of the layers it reacts to, two entities collide when either one's
+// mask covers the other's layer.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Layer(u32);
+
+impl Layer {
+    pub const NONE: Layer = Layer(0);
+    pub const PLAYER: Layer = Layer(1 << 0);
+    pub const PLAYER_PROJECTILE: Layer = Layer(1 << 1);
+    pub const ENEMY: Layer = Layer(1 << 2);
+    pub const ENEMY_PROJECTILE: Layer = Layer(1 << 3);
+    pub const PICKUP: Layer = Layer(1 << 4);
+    pub const HAZARD: Layer = Layer(1 << 5);
+
+    pub fn intersects(self, other: Layer) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Layer {
+    type Output = Layer;
+
+    fn bitor(self, other: Layer) -> Layer {
+        Layer(self.0 | other.0)
+    }
+}
+
+// what an entity gets to know about whatever it ran into
+pub struct Contact {
+    pub layer: Layer,
+    pub damage: i32,
+    pub center: (i32, i32),
+}
+
+// A hitbox, given in the sprite's own pixels the way the image file has
+// them. It gets placed over the sprite the way the sprite is drawn: scaled
+// up, turned and centered on the entity.
+#[derive(Clone)]
+pub enum Hitbox {
+    // a box within the sprite frame
+    Rect(Bounds),
+    // a circle around the middle of the sprite
+    Circle { radius: f32 },
+    // a convex outline
+    Polygon(Rc<Vec<(f32, f32)>>),
+}
+
+impl Hitbox {
+    // the box around `points`, e.g. the opaque pixels of a sprite
+    pub fn bounding(points: &[(i32, i32)]) -> Self {
+        let xs = points.iter().map(|point| point.0);
+        let ys = points.iter().map(|point| point.1);
+        match (xs.clone().min(), ys.clone().min(), xs.max(), ys.max()) {
+            (Some(x0), Some(y0), Some(x1), Some(y1)) => Hitbox::Rect(Bounds {
+                x: x0,
+                y: y0,
+                w: x1 - x0,
+                h: y1 - y0,
+            }),
+            _ => Hitbox::Polygon(Rc::new(vec![])),
+        }
+    }
+
+    // the convex hull around `points`
+    pub fn hull(points: &[(i32, i32)]) -> Self {
+        let points = points.iter().map(|&(x, y)| (x as f32, y as f32));
+        Hitbox::Polygon(Rc::new(convex_hull(points.collect())))
+    }
+
+    // place the hitbox over an entity drawn at `angle`, its base being the
+    // size of one sprite frame
+    pub fn place(&self, base: &EntityBase, angle: f64) -> Shape {
+        let (x, y) = base.center();
+        let center = (x as f32, y as f32);
+        let half = (base.width as f32 / 2.0, base.height as f32 / 2.0);
+        let scale = SCALE as f32;
+        let (sin, cos) = (angle as f32).to_radians().sin_cos();
+        let place = |(x, y): (f32, f32)| {
+            let (x, y) = ((x - half.0) * scale, (y - half.1) * scale);
+            (center.0 + x * cos - y * sin, center.1 + x * sin + y * cos)
+        };
+        match self {
+            Hitbox::Rect(bounds) => {
+                Shape::Polygon(corners(bounds).into_iter().map(place).collect())
+            }
+            Hitbox::Circle { radius } => Shape::Circle {
+                center,
+                radius: radius * scale,
+            },
+            Hitbox::Polygon(points) => Shape::Polygon(points.iter().copied().map(place).collect()),
+        }
+    }
+}
+
+// a hitbox placed in the world, in screen pixels
+#[derive(Clone)]
+pub enum Shape {
+    Circle { center: (f32, f32), radius: f32 },
+    // convex, a polygon without points never collides
+    Polygon(Vec<(f32, f32)>),
+}
+
+impl From<Bounds> for Shape {
+    fn from(bounds: Bounds) -> Self {
+        Shape::Polygon(corners(&bounds).to_vec())
+    }
+}
+
+impl Shape {
+    const CIRCLE_SEGMENTS: usize = 16;
+
+    // the box around the shape, for the broad-phase
+    pub fn bounds(&self) -> Bounds {
+        let (x0, y0, x1, y1) = match self {
+            Shape::Circle { center, radius } => (
+                center.0 - radius,
+                center.1 - radius,
+                center.0 + radius,
+                center.1 + radius,
+            ),
+            Shape::Polygon(points) if points.is_empty() => (0.0, 0.0, 0.0, 0.0),
+            Shape::Polygon(points) => points.iter().fold(
+                (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+                |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            ),
+        };
+        let (x, y) = (x0.floor() as i32, y0.floor() as i32);
+        Bounds {
+            x,
+            y,
+            w: x1.ceil() as i32 - x,
+            h: y1.ceil() as i32 - y,
+        }
+    }
+
+    // separating axis test, shapes that only touch don't overlap
+    pub fn overlaps(&self, other: &Shape) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+        let mut axes = self.axes(other);
+        axes.extend(other.axes(self));
+        axes.into_iter().all(|axis| {
+            let (min_a, max_a) = self.project(axis);
+            let (min_b, max_b) = other.project(axis);
+            min_a < max_b && min_b < max_a
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Shape::Polygon(points) if points.is_empty())
+    }
+
+    // axes that could separate this shape from `other`: the edge normals of
+    // a polygon, for a circle the direction to the nearest point of `other`
+    fn axes(&self, other: &Shape) -> Vec<(f32, f32)> {
+        let axes: Vec<(f32, f32)> = match self {
+            Shape::Polygon(points) => (0..points.len())
+                .map(|i| {
+                    let ((x0, y0), (x1, y1)) = (points[i], points[(i + 1) % points.len()]);
+                    (y0 - y1, x1 - x0)
+                })
+                .collect(),
+            Shape::Circle { center, .. } => {
+                let distance = |(x, y): (f32, f32)| (x - center.0).powi(2) + (y - center.1).powi(2);
+                let nearest = match other {
+                    Shape::Circle { center, .. } => Some(*center),
+                    Shape::Polygon(points) => points
+                        .iter()
+                        .copied()
+                        .min_by(|&a, &b| distance(a).total_cmp(&distance(b))),
+                };
+                nearest
+                    .map(|(x, y)| (x - center.0, y - center.1))
+                    .into_iter()
+                    .collect()
+            }
+        };
+        // repeated points give no direction to test along
+        axes.into_iter()
+            .filter(|&axis| axis != (0.0, 0.0))
+            .collect()
+    }
+
+    // the range the shape covers along `axis`
+    fn project(&self, axis: (f32, f32)) -> (f32, f32) {
+        let dot = |(x, y): (f32, f32)| x * axis.0 + y * axis.1;
+        match self {
+            Shape::Circle { center, radius } => {
+                let (middle, reach) = (dot(*center), radius * axis.0.hypot(axis.1));
+                (middle - reach, middle + reach)
+            }
+            Shape::Polygon(points) => points
+                .iter()
+                .map(|&point| dot(point))
+                .fold((f32::MAX, f32::MIN), |(min, max), d| {
+                    (min.min(d), max.max(d))
+                }),
+        }
+    }
+
+    // the area covered by the shape while moving by `motion` to where it
+    // is now, so fast movers can't skip past anything between two ticks
+    pub fn sweep(&self, motion: (f32, f32)) -> Shape {
+        if motion == (0.0, 0.0) || self.is_empty() {
+            return self.clone();
+        }
+        let outline = self.outline();
+        let start = outline.iter().map(|&(x, y)| (x - motion.0, y - motion.1));
+        let points = start.chain(outline.iter().copied()).collect();
+        Shape::Polygon(convex_hull(points))
+    }
+
+    // polygon points, circles are approximated
+    fn outline(&self) -> Vec<(f32, f32)> {
+        match self {
+            Shape::Circle { center, radius } => (0..Self::CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 * std::f32::consts::TAU / Self::CIRCLE_SEGMENTS as f32;
+                    (
+                        center.0 + radius * angle.cos(),
+                        center.1 + radius * angle.sin(),
+                    )
+                })
+                .collect(),
+            Shape::Polygon(points) => points.clone(),
+        }
+    }
+
+    // outline for the `RENDER_DEBUG` overlay
+    pub fn render(&self, canvas: &mut WindowCanvas) {
+        let mut points: Vec<Point> = self
+            .outline()
+            .into_iter()
+            .map(|(x, y)| Point::new(x.round() as i32, y.round() as i32))
+            .collect();
+        if let Some(&first) = points.first() {
+            points.push(first);
+        }
+        canvas.draw_lines(points.as_slice()).ok();
+    }
+}
+
+fn corners(bounds: &Bounds) -> [(f32, f32); 4] {
+    let (x0, y0) = (bounds.x as f32, bounds.y as f32);
+    let (x1, y1) = (x0 + bounds.w as f32, y0 + bounds.h as f32);
+    [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+}
+
+// the convex hull around `points`, built with the monotone chain
+fn convex_hull(mut points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    points.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    let cross = |o: (f32, f32), a: (f32, f32), b: (f32, f32)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut hull: Vec<(f32, f32)> = vec![];
+    // lower half left to right, then upper half back
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // the last point starts the other half
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: i32, y: i32, size: i32) -> Shape {
+        Bounds {
+            x,
+            y,
+            w: size,
+            h: size,
+        }
+        .into()
+    }
+
+    #[test]
+    fn hull_drops_inner_and_collinear_points() {
+        let hull = convex_hull(vec![
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (1.0, 2.0),
+            (0.0, 4.0),
+            (4.0, 4.0),
+        ]);
+        assert_eq!(hull, vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+    }
+
+    #[test]
+    fn touching_polygons_do_not_overlap() {
+        assert!(!square(0, 0, 10).overlaps(&square(10, 0, 10)));
+        assert!(square(0, 0, 10).overlaps(&square(9, 9, 10)));
+        // a triangle's slanted side keeps it clear of a box its bounds reach
+        let triangle = Shape::Polygon(vec![(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]);
+        assert!(!triangle.overlaps(&square(6, 6, 4)));
+        assert!(triangle.overlaps(&square(4, 4, 4)));
+    }
+
+    #[test]
+    fn circles_against_circles_and_polygons() {
+        let circle = |x, y, radius| Shape::Circle {
+            center: (x, y),
+            radius,
+        };
+        assert!(!circle(0.0, 0.0, 5.0).overlaps(&circle(10.0, 0.0, 5.0)));
+        assert!(circle(0.0, 0.0, 5.0).overlaps(&circle(9.0, 0.0, 5.0)));
+        // touching an edge, then reaching past it
+        assert!(!circle(15.0, 5.0, 5.0).overlaps(&square(0, 0, 10)));
+        assert!(circle(14.0, 5.0, 5.0).overlaps(&square(0, 0, 10)));
+        // off a corner, inside the box's bounds but clear of the box
+        assert!(!circle(14.0, 14.0, 5.0).overlaps(&square(0, 0, 10)));
+        assert!(circle(13.0, 13.0, 5.0).overlaps(&square(0, 0, 10)));
+    }
+
+    #[test]
+    fn sweep_catches_a_fast_bullet_crossing_a_thin_target() {
+        let target = Shape::from(Bounds {
+            x: 50,
+            y: 0,
+            w: 10,
+            h: 40,
+        });
+        // 22 px in one tick, from left of the target to right of it
+        let bullet = Shape::from(Bounds {
+            x: 62,
+            y: 18,
+            w: 4,
+            h: 4,
+        });
+        let motion = (22.0, 0.0);
+        assert!(!bullet.overlaps(&target));
+        assert!(bullet.sweep(motion).overlaps(&target));
+        // one that stops short, or flies past above, still misses
+        let short = Shape::from(Bounds {
+            x: 44,
+            y: 18,
+            w: 4,
+            h: 4,
+        });
+        assert!(!short.sweep(motion).overlaps(&target));
+        let above = Shape::from(Bounds {
+            x: 62,
+            y: -10,
+            w: 4,
+            h: 4,
+        });
+        assert!(!above.sweep(motion).overlaps(&target));
+    }
+
+    #[test]
+    fn sweep_of_a_circle_covers_its_path() {
+        let circle = Shape::Circle {
+            center: (100.0, 0.0),
+            radius: 3.0,
+        };
+        assert!(circle.sweep((60.0, 0.0)).overlaps(&square(60, -2, 4)));
+        assert!(!circle.sweep((0.0, 0.0)).overlaps(&square(60, -2, 4)));
+    }
+
+    #[test]
+    fn empty_polygons_never_overlap() {
+        assert!(!Shape::Polygon(vec![]).overlaps(&square(0, 0, 10)));
+    }
+}
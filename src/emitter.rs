@@ -0,0 +1,121 @@
+use serde::Deserialize;
+
+use crate::{
+    bullet::{Bullet, Side},
+    collision::Hitbox,
+    entity::EntityBase,
+    texture::ComponentTexture,
+};
+
+// Bullet emitters for enemy fire. Headings are in degrees, 0 points right
+// and angles grow clockwise since y grows downwards on screen.
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum Pattern {
+    // a single shot straight ahead
+    Forward,
+    // `count` shots `spread` degrees apart, centered on the player
+    Aimed { count: u32, spread: f32 },
+    // `count` shots evenly spaced all around
+    Radial { count: u32 },
+    // `arms` evenly spaced shots, turning by `step` degrees every volley
+    Spiral { arms: u32, step: f32 },
+    // `count` shots `spread` degrees apart, centered straight ahead
+    Fan { count: u32, spread: f32 },
+}
+
+pub struct Emitter {
+    pub pattern: Pattern,
+    // projectile speed in pixels per tick
+    pub speed: f32,
+    // how far a spiral has turned so far
+    rotation: f32,
+}
+
+impl Emitter {
+    pub fn new(pattern: Pattern, speed: f32) -> Self {
+        Self {
+            pattern,
+            speed,
+            rotation: 0.0,
+        }
+    }
+
+    // fire one volley from `origin` for a ship facing `facing`, returns the
+    // velocity of each shot
+    pub fn fire(
+        &mut self,
+        origin: (i32, i32),
+        facing: f32,
+        target: Option<(i32, i32)>,
+    ) -> Vec<(f32, f32)> {
+        let headings = match self.pattern {
+            Pattern::Forward => vec![facing],
+            Pattern::Aimed { count, spread } => {
+                let aim = match target {
+                    Some((x, y)) => ((y - origin.1) as f32)
+                        .atan2((x - origin.0) as f32)
+                        .to_degrees(),
+                    None => facing,
+                };
+                fan(aim, count, spread)
+            }
+            Pattern::Radial { count } => fan(facing, count, 360.0 / count as f32),
+            Pattern::Spiral { arms, step } => {
+                self.rotation = (self.rotation + step) % 360.0;
+                fan(facing + self.rotation, arms, 360.0 / arms as f32)
+            }
+            Pattern::Fan { count, spread } => fan(facing, count, spread),
+        };
+        headings
+            .into_iter()
+            .map(|heading| {
+                let heading = heading.to_radians();
+                (heading.cos() * self.speed, heading.sin() * self.speed)
+            })
+            .collect()
+    }
+
+    // fire a volley of enemy projectiles from the nose of `ship`
+    pub fn volley<'a>(
+        &mut self,
+        ship: &EntityBase,
+        facing: f32,
+        target: Option<(i32, i32)>,
+        damage: i32,
+        hitbox: &Hitbox,
+        texture: &ComponentTexture<'a>,
+    ) -> Vec<Bullet<'a>> {
+        let (width, height) = texture.size();
+        let origin = (ship.x, ship.center().1);
+        self.fire(origin, facing, target)
+            .into_iter()
+            .map(|velocity| {
+                let base = EntityBase::new(
+                    origin.0 - width as i32 / 2,
+                    origin.1 - height as i32 / 2,
+                    (width, height),
+                    velocity.0.round() as i32,
+                    velocity.1.round() as i32,
+                    ship.viewport,
+                );
+                Bullet::new(
+                    base,
+                    Side::Enemy,
+                    velocity,
+                    damage,
+                    hitbox.clone(),
+                    texture.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+// `count` headings `spread` degrees apart, centered on `center`
+fn fan(center: f32, count: u32, spread: f32) -> Vec<f32> {
+    let middle = (count as f32 - 1.0) / 2.0;
+    (0..count)
+        .map(|i| center + (i as f32 - middle) * spread)
+        .collect()
+}
@@ -1,47 +1,363 @@
-use sdl2::{rect::Rect, render::TextureCreator, video::WindowContext};
+use rand::Rng;
+use sdl2::rect::Rect;
+use serde::Deserialize;
 
 use crate::{
-    bullet::{Bullet, Side},
-    entity::{Entity, EntityBase, EntityEvent},
+    bullet::Bullet,
+    clock::Clock,
+    collision::{Contact, Hitbox, Layer, Shape},
+    emitter::{Emitter, Pattern},
+    entity::{Entity, EntityBase, EntityEvent, UpdateContext},
+    explosion::Explosion,
+    level::Spawn,
+    movement::{Movement, Mover},
     texture::{
-        ComponentTexture, ENEMY_BASE_TEXTURES, ENEMY_ENGINE_TEXTURES, ENEMY_PROJECTILE_TEXTURES,
+        ComponentTexture, TextureCache, ENEMY_BASE_TEXTURES, ENEMY_DESTRUCTION_TEXTURES,
+        ENEMY_ENGINE_TEXTURES, ENEMY_PROJECTILE_TEXTURES, ENEMY_SHIELD_TEXTURES,
+        ENEMY_WEAPON_TEXTURES,
     },
 };
 
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+pub enum EnemyKind {
+    Scout,
+    Fighter,
+    Frigate,
+    Bomber,
+    TorpedoShip,
+    SupportShip,
+    Battlecruiser,
+    Dreadnought,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProjectileKind {
+    Bullet,
+    BigBullet,
+    Wave,
+    Ray,
+    Torpedo,
+}
+
+// speeds are in pixels per tick
+pub struct EnemyStats {
+    pub hp: i32,
+    // hits soaked up by the shield before the hull takes damage
+    pub shield: u32,
+    pub speed: i32,
+    // milliseconds between two shots, `None` for unarmed ships
+    pub fire_interval: Option<u64>,
+    pub projectile: ProjectileKind,
+    pub bullet_speed: i32,
+    pub pattern: Pattern,
+    // how often this class shows up in random spawns, relative to the others
+    pub spawn_weight: u32,
+    pub movement: Movement,
+    // score for shooting one down, before the multiplier
+    pub points: u32,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 8] = [
+        EnemyKind::Scout,
+        EnemyKind::Fighter,
+        EnemyKind::Frigate,
+        EnemyKind::Bomber,
+        EnemyKind::TorpedoShip,
+        EnemyKind::SupportShip,
+        EnemyKind::Battlecruiser,
+        EnemyKind::Dreadnought,
+    ];
+
+    pub fn stats(&self) -> EnemyStats {
+        match self {
+            EnemyKind::Scout => EnemyStats {
+                hp: 1,
+                shield: 0,
+                speed: 5,
+                fire_interval: Some(800),
+                projectile: ProjectileKind::Bullet,
+                bullet_speed: 10,
+                pattern: Pattern::Forward,
+                spawn_weight: 30,
+                movement: Movement::Sine {
+                    amplitude: 60.0,
+                    period: 120,
+                },
+                points: 100,
+            },
+            EnemyKind::Fighter => EnemyStats {
+                hp: 2,
+                shield: 1,
+                speed: 4,
+                fire_interval: Some(600),
+                projectile: ProjectileKind::Bullet,
+                bullet_speed: 12,
+                pattern: Pattern::Aimed {
+                    count: 1,
+                    spread: 0.0,
+                },
+                spawn_weight: 25,
+                movement: Movement::Dive { steering: 0.05 },
+                points: 150,
+            },
+            EnemyKind::Frigate => EnemyStats {
+                hp: 4,
+                shield: 3,
+                speed: 3,
+                fire_interval: Some(1_000),
+                projectile: ProjectileKind::BigBullet,
+                bullet_speed: 8,
+                pattern: Pattern::Fan {
+                    count: 3,
+                    spread: 15.0,
+                },
+                spawn_weight: 12,
+                movement: Movement::StrafeHold {
+                    column: 900,
+                    hold: 240,
+                },
+                points: 400,
+            },
+            EnemyKind::Bomber => EnemyStats {
+                hp: 3,
+                shield: 1,
+                speed: 3,
+                fire_interval: Some(1_500),
+                projectile: ProjectileKind::Torpedo,
+                bullet_speed: 6,
+                pattern: Pattern::Forward,
+                spawn_weight: 10,
+                movement: Movement::Path {
+                    points: vec![(0, 0), (-300, 150), (-600, -100), (-900, 100), (-1_200, 0)],
+                },
+                points: 250,
+            },
+            EnemyKind::TorpedoShip => EnemyStats {
+                hp: 3,
+                shield: 1,
+                speed: 3,
+                fire_interval: Some(1_200),
+                projectile: ProjectileKind::Torpedo,
+                bullet_speed: 7,
+                pattern: Pattern::Aimed {
+                    count: 1,
+                    spread: 0.0,
+                },
+                spawn_weight: 10,
+                movement: Movement::StrafeHold {
+                    column: 1_000,
+                    hold: 180,
+                },
+                points: 300,
+            },
+            EnemyKind::SupportShip => EnemyStats {
+                hp: 2,
+                shield: 0,
+                speed: 4,
+                fire_interval: None,
+                projectile: ProjectileKind::Bullet,
+                bullet_speed: 0,
+                pattern: Pattern::Forward,
+                spawn_weight: 8,
+                movement: Movement::Circle {
+                    radius: 60.0,
+                    period: 180,
+                },
+                points: 200,
+            },
+            EnemyKind::Battlecruiser => EnemyStats {
+                hp: 12,
+                shield: 6,
+                speed: 2,
+                fire_interval: Some(1_400),
+                projectile: ProjectileKind::Wave,
+                bullet_speed: 6,
+                pattern: Pattern::Spiral {
+                    arms: 4,
+                    step: 15.0,
+                },
+                spawn_weight: 3,
+                movement: Movement::StrafeHold {
+                    column: 950,
+                    hold: 600,
+                },
+                points: 1_000,
+            },
+            EnemyKind::Dreadnought => EnemyStats {
+                hp: 16,
+                shield: 8,
+                speed: 1,
+                fire_interval: Some(1_000),
+                projectile: ProjectileKind::Ray,
+                bullet_speed: 14,
+                pattern: Pattern::Radial { count: 16 },
+                spawn_weight: 2,
+                movement: Movement::Straight,
+                points: 1_500,
+            },
+        }
+    }
+
+    // pick a class at random according to the spawn weights
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let total: u32 = Self::ALL.iter().map(|kind| kind.stats().spawn_weight).sum();
+        let mut pick = rng.gen_range(0..total);
+        for kind in Self::ALL {
+            let weight = kind.stats().spawn_weight;
+            if pick < weight {
+                return kind;
+            }
+            pick -= weight;
+        }
+        unreachable!()
+    }
+
+    pub fn index(&self) -> usize {
+        EnemyKind::ALL.iter().position(|kind| kind == self).unwrap()
+    }
+}
+
+impl ProjectileKind {
+    pub const ALL: [ProjectileKind; 5] = [
+        ProjectileKind::Bullet,
+        ProjectileKind::BigBullet,
+        ProjectileKind::Wave,
+        ProjectileKind::Ray,
+        ProjectileKind::Torpedo,
+    ];
+
+    pub fn damage(&self) -> i32 {
+        match self {
+            ProjectileKind::Bullet | ProjectileKind::Ray => 1,
+            ProjectileKind::BigBullet | ProjectileKind::Wave => 2,
+            ProjectileKind::Torpedo => 3,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        ProjectileKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap()
+    }
+}
+
 pub struct Enemy<'a> {
     base: EntityBase,
-    firing_ticks: u64,
+    mover: Mover,
+    emitter: Emitter,
+    pub kind: EnemyKind,
+    pub hp: i32,
+    pub shield: u32,
+    // simulated time of the last shot in milliseconds
+    last_fired: u64,
     engine_texture: ComponentTexture<'a>,
     body_texture: ComponentTexture<'a>,
+    hitbox: Hitbox,
+    weapon_texture: Option<ComponentTexture<'a>>,
     projectile_texture: ComponentTexture<'a>,
+    projectile_hitbox: Hitbox,
+    destruction_texture: ComponentTexture<'a>,
+    shield_texture: Option<ComponentTexture<'a>>,
+    // whether the shield is flashing from a hit
+    shield_flash: bool,
 }
 
 impl<'a> Entity<'a> for Enemy<'a> {
-    fn update(&mut self) -> EntityEvent<'a> {
-        if !self.base.update_x() {
+    fn update(&mut self, context: &UpdateContext) -> Vec<EntityEvent<'a>> {
+        self.mover.update(&mut self.base, context);
+        if !self.base.in_viewport() {
             self.base.valid = false;
         }
-        let ticks = unsafe { sdl2_sys::SDL_GetTicks64() };
-        if ticks - self.firing_ticks >= Self::FIRING_SPEED {
-            self.firing_ticks = ticks;
-            return EntityEvent::SpawnBullet(self.spawn_bullet(25));
+        let clock = &context.clock;
+        self.engine_texture.tick();
+        self.body_texture.tick();
+        if let Some(weapon_texture) = &mut self.weapon_texture {
+            weapon_texture.tick();
+        }
+        if let Some(shield_texture) = &mut self.shield_texture {
+            shield_texture.tick();
+            self.shield_flash &= !shield_texture.is_finished();
         }
-        EntityEvent::Empty
+        if let Some(fire_interval) = self.kind.stats().fire_interval {
+            if clock.millis() - self.last_fired >= fire_interval {
+                self.last_fired = clock.millis();
+                return self
+                    .fire(context.player)
+                    .into_iter()
+                    .map(EntityEvent::SpawnBullet)
+                    .collect();
+            }
+        }
+        vec![]
     }
 
-    fn render(&mut self, canvas: &mut sdl2::render::WindowCanvas) {
+    fn render(&mut self, canvas: &mut sdl2::render::WindowCanvas, alpha: f64) {
+        let position = self.base.render_position(alpha);
         self.engine_texture
-            .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
+            .render(position, Self::DEFAULT_ANGLE, canvas);
         self.body_texture
-            .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
+            .render(position, Self::DEFAULT_ANGLE, canvas);
+        if let Some(weapon_texture) = &mut self.weapon_texture {
+            weapon_texture.render(position, Self::DEFAULT_ANGLE, canvas);
+        }
+        if let Some(shield_texture) = &mut self.shield_texture {
+            if self.shield_flash {
+                shield_texture.render(position, Self::DEFAULT_ANGLE, canvas);
+            }
+        }
     }
 
     fn valid(&self) -> bool {
         self.base.valid
     }
 
-    fn is_enemy(&self) -> bool {
-        true
+    fn hitbox(&self) -> Option<Shape> {
+        Some(self.hitbox.place(&self.base, Self::DEFAULT_ANGLE))
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::ENEMY
+    }
+    fn mask(&self) -> Layer {
+        Layer::PLAYER | Layer::PLAYER_PROJECTILE
+    }
+
+    fn damage(&self) -> i32 {
+        Self::RAM_DAMAGE
+    }
+
+    fn on_collision(&mut self, other: &Contact) {
+        // the shield takes whole hits, whatever their damage
+        if self.shield > 0 {
+            self.shield -= 1;
+            if let Some(shield_texture) = &mut self.shield_texture {
+                shield_texture.restart();
+                self.shield_flash = true;
+            }
+            return;
+        }
+        self.hp -= other.damage;
+        if self.hp <= 0 {
+            self.base.valid = false;
+        }
+    }
+
+    // ships that were shot down blow up and score, ones that flew off
+    // screen don't
+    fn remains(&mut self) -> Vec<EntityEvent<'a>> {
+        if self.hp > 0 {
+            return vec![];
+        }
+        vec![
+            EntityEvent::SpawnExplosion(Explosion::new(
+                &self.base,
+                Self::DEFAULT_ANGLE,
+                self.destruction_texture.clone(),
+            )),
+            EntityEvent::Score(self.kind.stats().points),
+        ]
     }
 
     fn base(&self) -> Option<&EntityBase> {
@@ -53,55 +369,72 @@ impl<'a> Entity<'a> for Enemy<'a> {
 }
 
 impl<'a> Enemy<'a> {
-    const DEFAULT_SPEED: i32 = 4;
     const DEFAULT_ANGLE: f64 = -90.0;
-    const BULLET_SPEED: i32 = 10;
-    const FIRING_SPEED: u64 = 800;
-    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, viewport: Rect) -> Self {
-        let engine_texture = ComponentTexture::new(&texture_creator, &ENEMY_ENGINE_TEXTURES[0]);
-        let body_texture = ComponentTexture::new(&texture_creator, &ENEMY_BASE_TEXTURES[0]);
-        let projectile_texture =
-            ComponentTexture::new(&texture_creator, &ENEMY_PROJECTILE_TEXTURES[0]);
-        let y = (rand::random::<u32>() % viewport.height()) as i32;
-        let y = y.clamp(100, viewport.height() as i32 - 100);
-        let ticks = unsafe { sdl2_sys::SDL_GetTicks64() };
-        let (width, height) = body_texture.size();
-        let base = EntityBase {
-            x: viewport.width() as i32,
-            y,
-            width: width.try_into().unwrap(),
-            height: height.try_into().unwrap(),
-            dx: -(Self::DEFAULT_SPEED + (rand::random::<u32>() % 10) as i32 - 5),
-            dy: 0,
-            viewport,
-            valid: true,
-        };
+    // heading of the ship's guns, straight to the left
+    const FACING: f32 = 180.0;
+    const RAM_DAMAGE: i32 = 2;
+    // distance kept from the top and bottom of the screen when spawning
+    const SPAWN_MARGIN: i32 = 50;
+    pub fn new(
+        textures: &TextureCache<'a>,
+        spawn: &Spawn,
+        viewport: Rect,
+        clock: &Clock,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let kind = spawn.kind;
+        let stats = kind.stats();
+        let index = kind.index();
+        let engine_texture = textures.get(&ENEMY_ENGINE_TEXTURES[index]);
+        let body_texture = textures.get(&ENEMY_BASE_TEXTURES[index]);
+        let weapon_texture = ENEMY_WEAPON_TEXTURES[index]
+            .as_ref()
+            .map(|info| textures.get(info));
+        let projectile_info = &ENEMY_PROJECTILE_TEXTURES[stats.projectile.index()];
+        let projectile_texture = textures.get(projectile_info);
+        let destruction_texture = textures.get(&ENEMY_DESTRUCTION_TEXTURES[index]);
+        let shield_texture = ENEMY_SHIELD_TEXTURES[index]
+            .as_ref()
+            .map(|info| textures.get(info).play_once());
+        let (_, height) = body_texture.size();
+        let max_y = viewport.height() as i32 - height as i32 - Self::SPAWN_MARGIN;
+        let y = spawn.y.unwrap_or_else(|| {
+            rng.gen_range(Self::SPAWN_MARGIN..max_y.max(Self::SPAWN_MARGIN + 1))
+        });
+        let x = spawn.x.unwrap_or(viewport.width() as i32);
+        let speed = (stats.speed + rng.gen_range(-1..=1)).max(1);
+        let base = EntityBase::new(x, y, body_texture.size(), -speed, 0, viewport);
+        let movement = spawn.movement.clone().unwrap_or(stats.movement);
+        let pattern = spawn.pattern.unwrap_or(stats.pattern);
         Self {
             base,
-            firing_ticks: ticks,
+            mover: Mover::new(movement, (x, y), speed),
+            emitter: Emitter::new(pattern, stats.bullet_speed as f32),
+            kind,
+            hp: stats.hp,
+            shield: stats.shield,
+            last_fired: clock.millis(),
             engine_texture,
             body_texture,
+            hitbox: textures.hitbox(&ENEMY_BASE_TEXTURES[index], Hitbox::hull),
+            weapon_texture,
             projectile_texture,
+            projectile_hitbox: textures.hitbox(projectile_info, Hitbox::bounding),
+            destruction_texture,
+            shield_texture,
+            shield_flash: false,
         }
     }
-    pub fn spawn_bullet(&self, offset: i32) -> Bullet<'a> {
-        let (width, height) = self.projectile_texture.size();
-        let base = EntityBase {
-            x: self.base.x,
-            y: self.base.y + offset,
-            width: width.try_into().unwrap(),
-            height: height.try_into().unwrap(),
-            dx: -Self::BULLET_SPEED,
-            dy: 0,
-            viewport: self.base.viewport,
-            valid: true,
-        };
-
-        Bullet::new(
-            base,
-            Side::Enemy,
-            Self::DEFAULT_ANGLE,
-            self.projectile_texture.clone(),
+    // fire a volley from the nose of the ship, `target` is what aimed
+    // patterns go for
+    fn fire(&mut self, target: Option<(i32, i32)>) -> Vec<Bullet<'a>> {
+        self.emitter.volley(
+            &self.base,
+            Self::FACING,
+            target,
+            self.kind.stats().projectile.damage(),
+            &self.projectile_hitbox,
+            &self.projectile_texture,
         )
     }
 }
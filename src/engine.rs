@@ -0,0 +1,249 @@
+use std::str::FromStr;
+
+use sdl2::{
+    render::{TextureCreator, WindowCanvas},
+    video::WindowContext,
+};
+
+use crate::{
+    entity::EntityBase,
+    texture::{
+        ComponentTexture, ENGINE_EFFECTS_IDLE_TEXTURES, ENGINE_EFFECTS_POWERING_TEXTURES,
+        ENGINE_TEXTURES,
+    },
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EngineKind {
+    Base,
+    BigPulse,
+    Burst,
+    Supercharged,
+}
+
+// a short burst of speed in the direction the ship is steered
+pub struct Dash {
+    pub speed: f32,
+    pub ticks: u32,
+    pub cooldown: u32,
+}
+
+// speeds are in pixels per tick, acceleration and braking in pixels per tick²
+pub struct EngineStats {
+    pub top_speed: f32,
+    pub acceleration: f32,
+    // how quickly the ship slows down or turns around
+    pub braking: f32,
+    pub dash: Option<Dash>,
+}
+
+impl EngineKind {
+    pub const ALL: [EngineKind; 4] = [
+        EngineKind::Base,
+        EngineKind::BigPulse,
+        EngineKind::Burst,
+        EngineKind::Supercharged,
+    ];
+
+    pub fn stats(&self) -> EngineStats {
+        match self {
+            EngineKind::Base => EngineStats {
+                top_speed: 8.0,
+                acceleration: 8.0,
+                braking: 8.0,
+                dash: None,
+            },
+            EngineKind::BigPulse => EngineStats {
+                top_speed: 7.0,
+                acceleration: 1.2,
+                braking: 0.6,
+                dash: None,
+            },
+            EngineKind::Burst => EngineStats {
+                top_speed: 7.0,
+                acceleration: 1.5,
+                braking: 1.5,
+                dash: Some(Dash {
+                    speed: 20.0,
+                    ticks: 8,
+                    cooldown: 60,
+                }),
+            },
+            EngineKind::Supercharged => EngineStats {
+                top_speed: 12.0,
+                acceleration: 0.8,
+                braking: 0.5,
+                dash: None,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EngineKind::Base => "Base",
+            EngineKind::BigPulse => "Big Pulse",
+            EngineKind::Burst => "Burst",
+            EngineKind::Supercharged => "Supercharged",
+        }
+    }
+
+    // how it's spelled in options and replay files
+    pub fn id(&self) -> &'static str {
+        match self {
+            EngineKind::Base => "base",
+            EngineKind::BigPulse => "big-pulse",
+            EngineKind::Burst => "burst",
+            EngineKind::Supercharged => "supercharged",
+        }
+    }
+
+    fn index(&self) -> usize {
+        EngineKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap()
+    }
+}
+
+impl FromStr for EngineKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "base" => Ok(EngineKind::Base),
+            "big-pulse" => Ok(EngineKind::BigPulse),
+            "burst" => Ok(EngineKind::Burst),
+            "supercharged" => Ok(EngineKind::Supercharged),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct Engine<'a> {
+    pub kind: EngineKind,
+    base_texture: ComponentTexture<'a>,
+    powering_texture: ComponentTexture<'a>,
+    idle_texture: ComponentTexture<'a>,
+    velocity: (f32, f32),
+    // movement below a whole pixel, carried over to the next tick
+    remainder: (f32, f32),
+    dash_ticks: u32,
+    dash_cooldown: u32,
+}
+
+impl<'a> Engine<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, kind: EngineKind) -> Self {
+        let index = kind.index();
+        Self {
+            kind,
+            base_texture: ComponentTexture::new(texture_creator, &ENGINE_TEXTURES[index]),
+            powering_texture: ComponentTexture::new(
+                texture_creator,
+                &ENGINE_EFFECTS_POWERING_TEXTURES[index],
+            ),
+            idle_texture: ComponentTexture::new(
+                texture_creator,
+                &ENGINE_EFFECTS_IDLE_TEXTURES[index],
+            ),
+            velocity: (0.0, 0.0),
+            remainder: (0.0, 0.0),
+            dash_ticks: 0,
+            dash_cooldown: 0,
+        }
+    }
+
+    // start a dash if the engine has one and it's ready
+    pub fn dash(&mut self) {
+        if let Some(dash) = self.kind.stats().dash {
+            if self.dash_cooldown == 0 {
+                self.dash_ticks = dash.ticks;
+                self.dash_cooldown = dash.cooldown;
+            }
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.velocity = (0.0, 0.0);
+        self.remainder = (0.0, 0.0);
+        self.dash_ticks = 0;
+    }
+
+    // move `base` one tick, `input` is the steering direction with each axis
+    // in -1..=1
+    pub fn update(&mut self, base: &mut EntityBase, input: (i32, i32)) {
+        let stats = self.kind.stats();
+        let approach = |velocity: f32, input: i32| {
+            let target = input as f32 * stats.top_speed;
+            // speeding up in the current direction uses acceleration,
+            // slowing down or turning around uses braking
+            let rate = if velocity == 0.0 || (target != 0.0 && target.signum() == velocity.signum())
+            {
+                stats.acceleration
+            } else {
+                stats.braking
+            };
+            if velocity < target {
+                (velocity + rate).min(target)
+            } else {
+                (velocity - rate).max(target)
+            }
+        };
+        self.velocity = (
+            approach(self.velocity.0, input.0),
+            approach(self.velocity.1, input.1),
+        );
+        self.dash_cooldown = self.dash_cooldown.saturating_sub(1);
+        if self.dash_ticks > 0 {
+            self.dash_ticks -= 1;
+            if let Some(dash) = stats.dash {
+                // dash forward when not steering
+                let direction = if input == (0, 0) { (1, 0) } else { input };
+                self.velocity = (
+                    direction.0 as f32 * dash.speed,
+                    direction.1 as f32 * dash.speed,
+                );
+            }
+        }
+
+        let step = |remainder: &mut f32, velocity: f32| {
+            *remainder += velocity;
+            let step = remainder.trunc();
+            *remainder -= step;
+            step as i32
+        };
+        base.dx = step(&mut self.remainder.0, self.velocity.0);
+        base.dy = step(&mut self.remainder.1, self.velocity.1);
+        // stop against the edges of the viewport
+        if !base.update_x() {
+            self.velocity.0 = 0.0;
+        }
+        if !base.update_y() {
+            self.velocity.1 = 0.0;
+        }
+
+        self.base_texture.tick();
+        if input != (0, 0) {
+            self.powering_texture.tick();
+        } else {
+            self.idle_texture.tick();
+        }
+    }
+
+    pub fn render_base(&mut self, position: (i32, i32), angle: f64, canvas: &mut WindowCanvas) {
+        self.base_texture.render(position, angle, canvas);
+    }
+
+    pub fn render_effect(
+        &mut self,
+        position: (i32, i32),
+        angle: f64,
+        powering: bool,
+        canvas: &mut WindowCanvas,
+    ) {
+        if powering {
+            self.powering_texture.render(position, angle, canvas);
+        } else {
+            self.idle_texture.render(position, angle, canvas);
+        }
+    }
+}
@@ -1,17 +1,37 @@
 use sdl2::{event::Event, rect::Rect, render::WindowCanvas};
 
-use crate::bullet::Bullet;
+use crate::{
+    bullet::Bullet,
+    clock::Clock,
+    collision::{Contact, Layer, Shape},
+    explosion::Explosion,
+    grid::Bounds,
+    player::Status,
+};
 
 pub enum EntityEvent<'a> {
     SpawnBullet(Bullet<'a>),
-    Empty,
+    SpawnExplosion(Explosion<'a>),
+    // points for a ship shot down
+    Score(u32),
+}
+
+// what entities get to see of the world on each tick
+pub struct UpdateContext {
+    pub clock: Clock,
+    // center of the player's ship, if there is one
+    pub player: Option<(i32, i32)>,
 }
 
 pub struct EntityBase {
     pub x: i32,
     pub y: i32,
+    // position at the start of the current tick, used to interpolate rendering
+    pub prev_x: i32,
+    pub prev_y: i32,
     pub width: i32,
     pub height: i32,
+    // velocity in pixels per tick
     pub dx: i32,
     pub dy: i32,
     pub viewport: Rect,
@@ -19,26 +39,53 @@ pub struct EntityBase {
 }
 
 impl EntityBase {
-    pub fn update_x(&mut self) -> bool {
-        let new_x = self.x + self.dx;
-        if new_x <= self.viewport.x + self.viewport.w && new_x + self.width >= self.viewport.x {
-            self.x = new_x;
-            true
-        } else {
-            false
+    pub fn new(x: i32, y: i32, size: (u32, u32), dx: i32, dy: i32, viewport: Rect) -> Self {
+        Self {
+            x,
+            y,
+            prev_x: x,
+            prev_y: y,
+            width: size.0.try_into().unwrap(),
+            height: size.1.try_into().unwrap(),
+            dx,
+            dy,
+            viewport,
+            valid: true,
         }
     }
-    pub fn update_y(&mut self) -> bool {
-        let new_y = self.y + self.dy;
-        if new_y <= self.viewport.y + self.viewport.h && new_y + self.height >= self.viewport.y {
-            self.y = new_y;
-            true
-        } else {
-            false
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            x: self.x,
+            y: self.y,
+            w: self.width,
+            h: self.height,
         }
     }
-    pub fn update_x_rev(&mut self) -> bool {
-        let new_x = self.x - self.dx;
+    pub fn center(&self) -> (i32, i32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+    // how far the entity moved during the last tick
+    pub fn motion(&self) -> (i32, i32) {
+        (self.x - self.prev_x, self.y - self.prev_y)
+    }
+    pub fn save_position(&mut self) {
+        self.prev_x = self.x;
+        self.prev_y = self.y;
+    }
+    // position between the last two ticks, `alpha` is in 0..1
+    pub fn render_position(&self, alpha: f64) -> (i32, i32) {
+        let lerp = |prev: i32, cur: i32| prev + ((cur - prev) as f64 * alpha).round() as i32;
+        (lerp(self.prev_x, self.x), lerp(self.prev_y, self.y))
+    }
+    // whether any part of the entity is still on screen
+    pub fn in_viewport(&self) -> bool {
+        self.x <= self.viewport.x + self.viewport.w
+            && self.x + self.width >= self.viewport.x
+            && self.y <= self.viewport.y + self.viewport.h
+            && self.y + self.height >= self.viewport.y
+    }
+    pub fn update_x(&mut self) -> bool {
+        let new_x = self.x + self.dx;
         if new_x <= self.viewport.x + self.viewport.w && new_x + self.width >= self.viewport.x {
             self.x = new_x;
             true
@@ -46,8 +93,8 @@ impl EntityBase {
             false
         }
     }
-    pub fn update_y_rev(&mut self) -> bool {
-        let new_y = self.y - self.dy;
+    pub fn update_y(&mut self) -> bool {
+        let new_y = self.y + self.dy;
         if new_y <= self.viewport.y + self.viewport.h && new_y + self.height >= self.viewport.y {
             self.y = new_y;
             true
@@ -58,22 +105,52 @@ impl EntityBase {
 }
 
 pub trait Entity<'a> {
-    fn render(&mut self, canvas: &mut WindowCanvas);
+    fn render(&mut self, canvas: &mut WindowCanvas, alpha: f64);
     #[allow(unused)]
     fn handle_event(&mut self, event: Event) {}
-    fn update(&mut self) -> EntityEvent<'a> {
-        EntityEvent::Empty
+    #[allow(unused)]
+    fn update(&mut self, context: &UpdateContext) -> Vec<EntityEvent<'a>> {
+        vec![]
     }
     fn valid(&self) -> bool;
-    fn is_bullet(&self) -> bool {
-        false
+    // damage dealt to whatever this entity runs into
+    fn damage(&self) -> i32 {
+        1
     }
-    fn is_player(&self) -> bool {
+    // the collision layer this entity sits on
+    fn layer(&self) -> Layer {
+        Layer::NONE
+    }
+    // the layers this entity reacts to
+    fn mask(&self) -> Layer {
+        Layer::NONE
+    }
+    // where the entity can be hit, defaults to its whole base
+    fn hitbox(&self) -> Option<Shape> {
+        self.base().map(|base| base.bounds().into())
+    }
+    // called when this entity runs into something on a layer in its mask
+    #[allow(unused)]
+    fn on_collision(&mut self, other: &Contact) {
+        if let Some(base) = self.base_mut() {
+            base.valid = false;
+        }
+    }
+    // whatever is left behind once this entity is removed
+    fn remains(&mut self) -> Vec<EntityEvent<'a>> {
+        vec![]
+    }
+    // hits against an invincible entity are ignored
+    fn is_invincible(&self) -> bool {
         false
     }
-    fn is_enemy(&self) -> bool {
+    fn is_boss(&self) -> bool {
         false
     }
+    // the ship's state for the HUD, only the player has one
+    fn status(&self) -> Option<Status> {
+        None
+    }
     #[allow(unused)]
     fn base(&self) -> Option<&EntityBase> {
         None
@@ -0,0 +1,52 @@
+use sdl2::render::WindowCanvas;
+
+use crate::{
+    entity::{Entity, EntityBase, EntityEvent, UpdateContext},
+    texture::ComponentTexture,
+};
+
+// A destruction animation left behind by a ship, plays once and goes away.
+// It doesn't take part in collisions.
+pub struct Explosion<'a> {
+    base: EntityBase,
+    angle: f64,
+    texture: ComponentTexture<'a>,
+}
+
+impl<'a> Explosion<'a> {
+    // play `texture` over the ship at `base`
+    pub fn new(base: &EntityBase, angle: f64, texture: ComponentTexture<'a>) -> Self {
+        let base = EntityBase::new(base.x, base.y, texture.size(), 0, 0, base.viewport);
+        Self {
+            base,
+            angle,
+            texture: texture.play_once(),
+        }
+    }
+}
+
+impl<'a> Entity<'a> for Explosion<'a> {
+    fn update(&mut self, _context: &UpdateContext) -> Vec<EntityEvent<'a>> {
+        self.texture.tick();
+        if self.texture.is_finished() {
+            self.base.valid = false;
+        }
+        vec![]
+    }
+
+    fn render(&mut self, canvas: &mut WindowCanvas, alpha: f64) {
+        self.texture
+            .render(self.base.render_position(alpha), self.angle, canvas);
+    }
+
+    fn valid(&self) -> bool {
+        self.base.valid
+    }
+
+    fn base(&self) -> Option<&EntityBase> {
+        Some(&self.base)
+    }
+    fn base_mut(&mut self) -> Option<&mut EntityBase> {
+        Some(&mut self.base)
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+// Uniform grid broad-phase for collisions: boxes are bucketed into square
+// cells and only boxes sharing a cell get compared. Kept free of any game
+// types so the benchmark can use it on its own.
+
+// axis-aligned box in pixels
+#[derive(Clone, Copy)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Bounds {
+    pub fn overlaps(&self, other: &Bounds) -> bool {
+        self.x.max(other.x) < (self.x + self.w).min(other.x + other.w)
+            && self.y.max(other.y) < (self.y + self.h).min(other.y + other.h)
+    }
+
+    // range of cells covered, inclusive on both ends
+    fn cells(&self, cell_size: i32) -> ((i32, i32), (i32, i32)) {
+        let cell = |v: i32| v.div_euclid(cell_size);
+        (
+            (cell(self.x), cell(self.y)),
+            (
+                cell(self.x + self.w.max(1) - 1),
+                cell(self.y + self.h.max(1) - 1),
+            ),
+        )
+    }
+}
+
+pub struct Grid {
+    cell_size: i32,
+    // everything inserted, as (id, bounds)
+    entries: Vec<(usize, Bounds)>,
+    // positions in `entries` of the boxes touching each cell
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Grid {
+    pub fn new(cell_size: i32) -> Self {
+        Self {
+            cell_size,
+            entries: vec![],
+            cells: HashMap::new(),
+        }
+    }
+
+    // empty the grid, keeping its allocations for the next tick
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, id: usize, bounds: Bounds) {
+        let entry = self.entries.len();
+        self.entries.push((id, bounds));
+        let ((x0, y0), (x1, y1)) = bounds.cells(self.cell_size);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                self.cells.entry((x, y)).or_default().push(entry);
+            }
+        }
+    }
+
+    // ids of every two overlapping boxes, each pair reported once with the
+    // lower id first, sorted so collisions resolve in the same order on
+    // every run
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for (&cell, entries) in &self.cells {
+            for (i, &a) in entries.iter().enumerate() {
+                for &b in &entries[i + 1..] {
+                    let ((id_a, bounds_a), (id_b, bounds_b)) = (self.entries[a], self.entries[b]);
+                    if !bounds_a.overlaps(&bounds_b) {
+                        continue;
+                    }
+                    // boxes can share several cells, only the top left one of
+                    // their overlap reports them
+                    let ((ax, ay), _) = bounds_a.cells(self.cell_size);
+                    let ((bx, by), _) = bounds_b.cells(self.cell_size);
+                    if cell == (ax.max(bx), ay.max(by)) {
+                        pairs.push((id_a.min(id_b), id_a.max(id_b)));
+                    }
+                }
+            }
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // paths are spelled out, the benchmark includes this file as well and
+    // would flag a `use` as unused
+
+    #[test]
+    fn pair_spanning_several_cells_is_reported_once() {
+        let bounds = |x, y, w, h| super::Bounds { x, y, w, h };
+        let mut grid = super::Grid::new(16);
+        // both boxes cover the same 3x3 block of cells
+        grid.insert(0, bounds(2, 2, 40, 40));
+        grid.insert(1, bounds(8, 8, 36, 36));
+        assert_eq!(grid.pairs(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn boxes_sharing_a_cell_without_touching_are_not_paired() {
+        let bounds = |x, y, w, h| super::Bounds { x, y, w, h };
+        let mut grid = super::Grid::new(64);
+        grid.insert(0, bounds(0, 0, 10, 10));
+        grid.insert(1, bounds(20, 20, 10, 10));
+        // touching edges don't count as overlapping
+        grid.insert(2, bounds(10, 0, 10, 10));
+        assert_eq!(grid.pairs(), vec![]);
+    }
+
+    #[test]
+    fn matches_pairwise_checks() {
+        let bounds = |x, y, w, h| super::Bounds { x, y, w, h };
+        // small linear congruential generator, enough to scatter boxes
+        let mut state = 12345u32;
+        let mut next = |range: i32| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as i32 % range
+        };
+        let boxes: Vec<super::Bounds> = (0..300)
+            .map(|_| bounds(next(1280) - 40, next(720) - 40, 1 + next(80), 1 + next(80)))
+            .collect();
+        let mut grid = super::Grid::new(32);
+        for (id, bounds) in boxes.iter().enumerate() {
+            grid.insert(id, *bounds);
+        }
+        let mut expected = vec![];
+        for (i, a) in boxes.iter().enumerate() {
+            for (j, b) in boxes.iter().enumerate().skip(i + 1) {
+                if a.overlaps(b) {
+                    expected.push((i, j));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(grid.pairs(), expected);
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::ErrorKind;
+
+use serde::{Deserialize, Serialize};
+
+use crate::options::Difficulty;
+
+// Local high scores, one table for every combination of seed, level and
+// difficulty since only runs under the same conditions compare. Kept as a
+// RON file next to the game.
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conditions {
+    pub seed: u64,
+    // name of the level, `None` for random endless waves
+    pub level: Option<String>,
+    pub difficulty: Difficulty,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub score: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Table {
+    conditions: Conditions,
+    // best first
+    entries: Vec<Entry>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct HighScores {
+    tables: Vec<Table>,
+    // set when the file couldn't be loaded, so saving doesn't wipe it
+    #[serde(skip)]
+    is_read_only: bool,
+}
+
+impl HighScores {
+    // entries kept in each table
+    const SIZE: usize = 10;
+
+    // a missing or unreadable table starts out empty rather than refusing to start
+    pub fn load(path: &str) -> Self {
+        let result = match std::fs::read_to_string(path) {
+            Ok(content) => ron::from_str(&content).map_err(|e| format!("{path}: {e}")),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("can't read `{path}`: {e}")),
+        };
+        result.unwrap_or_else(|e| {
+            eprintln!("{e}, starting with empty high scores that won't be saved");
+            Self {
+                is_read_only: true,
+                ..Self::default()
+            }
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if self.is_read_only {
+            return Err(format!(
+                "not saving high scores over `{path}`, it couldn't be loaded"
+            ));
+        }
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("can't write `{path}`: {e}"))
+    }
+
+    pub fn entries(&self, conditions: &Conditions) -> &[Entry] {
+        self.table(conditions)
+            .map_or(&[], |table| table.entries.as_slice())
+    }
+
+    // whether `score` would make it into the table
+    pub fn qualifies(&self, conditions: &Conditions, score: u64) -> bool {
+        let entries = self.entries(conditions);
+        score > 0 && (entries.len() < Self::SIZE || entries.iter().any(|entry| score > entry.score))
+    }
+
+    // add an entry, returns its rank if it made it into the table
+    pub fn insert(&mut self, conditions: &Conditions, entry: Entry) -> Option<usize> {
+        if !self.qualifies(conditions, entry.score) {
+            return None;
+        }
+        if self.table(conditions).is_none() {
+            self.tables.push(Table {
+                conditions: conditions.clone(),
+                entries: vec![],
+            });
+        }
+        let table = self
+            .tables
+            .iter_mut()
+            .find(|table| &table.conditions == conditions)?;
+        // ties go to whoever got there first
+        let rank = table
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(table.entries.len());
+        table.entries.insert(rank, entry);
+        table.entries.truncate(Self::SIZE);
+        Some(rank)
+    }
+
+    fn table(&self, conditions: &Conditions) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|table| &table.conditions == conditions)
+    }
+}
@@ -0,0 +1,238 @@
+use sdl2::{
+    pixels::Color,
+    rect::Rect,
+    render::{TextureCreator, WindowCanvas},
+    ttf::{Font, Sdl2TtfContext},
+    video::WindowContext,
+};
+
+use crate::{
+    clock::Clock,
+    highscore::{Conditions, Entry},
+    player::Status,
+    score::Score,
+    state::LOADOUT_ROWS,
+};
+
+#[derive(Clone, Copy)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// Score, multiplier and the state of the player's ship, drawn over the game
+pub struct Hud<'a> {
+    font: Font<'a, 'static>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    viewport: Rect,
+}
+
+impl<'a> Hud<'a> {
+    const FONT: &'static str = "assets/fonts/DejaVuSansMono-Bold.ttf";
+    const FONT_SIZE: u16 = 22;
+    const MARGIN: i32 = 16;
+    const LINE_SPACING: i32 = 8;
+    const TEXT_COLOR: Color = Color::RGB(255, 255, 255);
+    const HIGHLIGHT_COLOR: Color = Color::RGB(255, 200, 40);
+    // bar under the multiplier showing how long until it drops
+    const CHAIN_BAR_SIZE: (u32, u32) = (120, 6);
+
+    pub fn new(
+        ttf: &'a Sdl2TtfContext,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        viewport: Rect,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            font: ttf.load_font(Self::FONT, Self::FONT_SIZE)?,
+            texture_creator,
+            viewport,
+        })
+    }
+
+    pub fn render(
+        &self,
+        canvas: &mut WindowCanvas,
+        score: &Score,
+        clock: &Clock,
+        status: Option<&Status>,
+    ) {
+        let line = self.font.height();
+        let (left, right) = (
+            self.viewport.x + Self::MARGIN,
+            self.viewport.x + self.viewport.w - Self::MARGIN,
+        );
+        let top = self.viewport.y + Self::MARGIN;
+        self.text(
+            canvas,
+            &format!("SCORE {:08}", score.points),
+            (left, top),
+            Align::Left,
+        );
+        self.text(
+            canvas,
+            &format!("x{}", score.multiplier()),
+            (left, top + line),
+            Align::Left,
+        );
+        let chain_left = score.chain_left(clock);
+        if chain_left > 0.0 {
+            let (width, height) = Self::CHAIN_BAR_SIZE;
+            let filled = (width as f32 * chain_left).ceil() as u32;
+            let color = canvas.draw_color();
+            canvas.set_draw_color(Self::HIGHLIGHT_COLOR);
+            canvas
+                .fill_rect(Rect::new(left, top + line * 2 + 4, filled, height))
+                .ok();
+            canvas.set_draw_color(color);
+        }
+
+        let Some(status) = status else { return };
+        self.text(
+            canvas,
+            &format!("LIVES {}", status.lives),
+            (right, top),
+            Align::Right,
+        );
+        self.text(
+            canvas,
+            &format!("HP {}/{}", status.hp, status.max_hp),
+            (right, top + line),
+            Align::Right,
+        );
+        let bottom = self.viewport.y + self.viewport.h - Self::MARGIN - line;
+        self.text(canvas, status.weapon.name(), (left, bottom), Align::Left);
+    }
+
+    pub fn render_title(&self, canvas: &mut WindowCanvas) {
+        self.render_panel(
+            canvas,
+            &[
+                ("SHOOT".to_string(), Self::HIGHLIGHT_COLOR),
+                ("press Enter to start".to_string(), Self::TEXT_COLOR),
+                ("press Esc to quit".to_string(), Self::TEXT_COLOR),
+            ],
+        );
+    }
+
+    // one line per entry of `LOADOUT_ROWS` with what's picked for it,
+    // `selected` is the line being changed
+    pub fn render_loadout(&self, canvas: &mut WindowCanvas, values: &[&str], selected: usize) {
+        let mut lines = vec![("LOADOUT".to_string(), Self::HIGHLIGHT_COLOR)];
+        for (row, (label, value)) in LOADOUT_ROWS.iter().zip(values).enumerate() {
+            let (text, color) = if row == selected {
+                (
+                    format!("{label:<10} < {value:^14} >"),
+                    Self::HIGHLIGHT_COLOR,
+                )
+            } else {
+                (format!("{label:<10}   {value:^14}  "), Self::TEXT_COLOR)
+            };
+            lines.push((text, color));
+        }
+        lines.push((
+            "Up/Down to pick, Left/Right to change, Enter to launch".to_string(),
+            Self::TEXT_COLOR,
+        ));
+        self.render_panel(canvas, &lines);
+    }
+
+    // the screen to type in a name for a new high score
+    pub fn render_name_entry(&self, canvas: &mut WindowCanvas, score: u64, name: &str) {
+        self.render_panel(
+            canvas,
+            &[
+                ("NEW HIGH SCORE".to_string(), Self::HIGHLIGHT_COLOR),
+                (format!("{score:08}"), Self::TEXT_COLOR),
+                (format!("NAME {name}_"), Self::TEXT_COLOR),
+                ("press Enter when done".to_string(), Self::TEXT_COLOR),
+            ],
+        );
+    }
+
+    // the high scores for a run's conditions, `highlight` is the rank of
+    // the run's own entry
+    pub fn render_leaderboard(
+        &self,
+        canvas: &mut WindowCanvas,
+        conditions: &Conditions,
+        entries: &[Entry],
+        highlight: Option<usize>,
+    ) {
+        let mut lines = vec![
+            ("HIGH SCORES".to_string(), Self::HIGHLIGHT_COLOR),
+            (
+                format!(
+                    "seed {}  {}  {}",
+                    conditions.seed,
+                    conditions.level.as_deref().unwrap_or("endless"),
+                    conditions.difficulty.name()
+                ),
+                Self::TEXT_COLOR,
+            ),
+        ];
+        for (rank, entry) in entries.iter().enumerate() {
+            let color = if highlight == Some(rank) {
+                Self::HIGHLIGHT_COLOR
+            } else {
+                Self::TEXT_COLOR
+            };
+            lines.push((
+                format!("{:>2}. {:<12} {:08}", rank + 1, entry.name, entry.score),
+                color,
+            ));
+        }
+        if entries.is_empty() {
+            lines.push(("no scores yet".to_string(), Self::TEXT_COLOR));
+        }
+        lines.push(("press Enter to continue".to_string(), Self::TEXT_COLOR));
+        self.render_panel(canvas, &lines);
+    }
+
+    // lines of text centered on a dimmed screen
+    fn render_panel(&self, canvas: &mut WindowCanvas, lines: &[(String, Color)]) {
+        let color = canvas.draw_color();
+        canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 180));
+        canvas.fill_rect(self.viewport).ok();
+        canvas.set_draw_color(color);
+
+        let line = self.font.height() + Self::LINE_SPACING;
+        let center = self.viewport.x + self.viewport.w / 2;
+        let mut y = self.viewport.y + (self.viewport.h - line * lines.len() as i32) / 2;
+        for (text, color) in lines {
+            self.text_colored(canvas, text, *color, (center, y), Align::Center);
+            y += line;
+        }
+    }
+
+    fn text(&self, canvas: &mut WindowCanvas, text: &str, position: (i32, i32), align: Align) {
+        self.text_colored(canvas, text, Self::TEXT_COLOR, position, align);
+    }
+
+    // draw `text` with the top of its `align` side at `position`
+    fn text_colored(
+        &self,
+        canvas: &mut WindowCanvas,
+        text: &str,
+        color: Color,
+        position: (i32, i32),
+        align: Align,
+    ) {
+        let Ok(surface) = self.font.render(text).blended(color) else {
+            return;
+        };
+        let Ok(texture) = self.texture_creator.create_texture_from_surface(&surface) else {
+            return;
+        };
+        let (width, height) = (surface.width(), surface.height());
+        let x = match align {
+            Align::Left => position.0,
+            Align::Center => position.0 - width as i32 / 2,
+            Align::Right => position.0 - width as i32,
+        };
+        canvas
+            .copy(&texture, None, Rect::new(x, position.1, width, height))
+            .ok();
+    }
+}
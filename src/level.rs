@@ -0,0 +1,91 @@
+use serde::Deserialize;
+
+use crate::{boss::BossKind, emitter::Pattern, enemy::EnemyKind, movement::Movement};
+
+// Levels are RON files listing timed waves, see `levels/` for examples.
+// Once the last wave is out the game carries on with random spawns.
+
+#[derive(Deserialize)]
+pub struct Level {
+    pub name: String,
+    pub waves: Vec<Wave>,
+}
+
+#[derive(Deserialize)]
+pub struct Wave {
+    // milliseconds since the start of the level
+    pub time: u64,
+    #[serde(default)]
+    pub enemies: Vec<Spawn>,
+    #[serde(default)]
+    pub boss: Option<BossKind>,
+}
+
+// one enemy of a wave, anything left out falls back to the class defaults
+// or a random spot along the right edge of the screen
+#[derive(Deserialize)]
+pub struct Spawn {
+    pub kind: EnemyKind,
+    #[serde(default)]
+    pub x: Option<i32>,
+    #[serde(default)]
+    pub y: Option<i32>,
+    #[serde(default)]
+    pub movement: Option<Movement>,
+    #[serde(default)]
+    pub pattern: Option<Pattern>,
+}
+
+impl Spawn {
+    pub fn random(kind: EnemyKind) -> Self {
+        Self {
+            kind,
+            x: None,
+            y: None,
+            movement: None,
+            pattern: None,
+        }
+    }
+}
+
+// plays the waves of a level in order as the clock reaches them
+pub struct Script {
+    pub level: Level,
+    // the file it was loaded from
+    pub path: String,
+    next_wave: usize,
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("can't read `{path}`: {e}"))?;
+        let mut level: Level = ron::from_str(&content).map_err(|e| format!("{path}: {e}"))?;
+        level.waves.sort_by_key(|wave| wave.time);
+        Ok(Self {
+            level,
+            path: path.to_string(),
+            next_wave: 0,
+        })
+    }
+
+    // play the level again from the start
+    pub fn restart(&mut self) {
+        self.next_wave = 0;
+    }
+
+    // the waves due by `millis` that haven't been sent yet
+    pub fn take_waves(&mut self, millis: u64) -> &[Wave] {
+        let start = self.next_wave;
+        while self.next_wave < self.level.waves.len()
+            && self.level.waves[self.next_wave].time <= millis
+        {
+            self.next_wave += 1;
+        }
+        &self.level.waves[start..self.next_wave]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_wave >= self.level.waves.len()
+    }
+}
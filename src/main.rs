@@ -1,12 +1,47 @@
 use app::App;
+use options::Options;
 
 mod app;
+mod boss;
 mod bullet;
+mod clock;
+mod collision;
+mod emitter;
+mod enemy;
+mod engine;
 mod entity;
+mod explosion;
+mod grid;
+mod highscore;
+mod hud;
+mod level;
+mod movement;
+mod options;
 mod player;
+mod replay;
+mod score;
+mod shield;
+mod state;
 mod texture;
-mod enemy;
+mod weapon;
 fn main() {
-    let mut app = App::new("shoot").unwrap();
-    app.run().ok();
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{}", Options::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let mut app = match App::new("shoot", options) {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = app.run() {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,172 @@
+use std::f32::consts::TAU;
+
+use serde::Deserialize;
+
+use crate::entity::{EntityBase, UpdateContext};
+
+// Flight patterns for enemies. All of them drift towards the left edge of
+// the screen at the ship's speed, distances are in pixels and durations in
+// ticks.
+#[derive(Clone, Deserialize)]
+pub enum Movement {
+    Straight,
+    // weave up and down around the spawn height
+    Sine { amplitude: f32, period: u64 },
+    // steer towards the player until passing them, `steering` is the share
+    // of the course correction applied every tick
+    Dive { steering: f32 },
+    // fly in to `column`, strafe up and down there for `hold` ticks, then leave
+    StrafeHold { column: i32, hold: u64 },
+    // loop around a center drifting left
+    Circle { radius: f32, period: u64 },
+    // follow a smooth curve through `points`, given relative to the spawn
+    // point, then keep going straight
+    Path { points: Vec<(i32, i32)> },
+}
+
+pub struct Mover {
+    movement: Movement,
+    speed: f32,
+    age: u64,
+    origin: (f32, f32),
+    position: (f32, f32),
+    velocity: (f32, f32),
+    // Path: index of the current segment and progress along it in 0..1
+    progress: (usize, f32),
+    // StrafeHold: tick the ship reached its column
+    arrived: Option<u64>,
+}
+
+impl Mover {
+    const STRAFE_AMPLITUDE: f32 = 80.0;
+    const STRAFE_PERIOD: f32 = 180.0;
+
+    pub fn new(movement: Movement, start: (i32, i32), speed: i32) -> Self {
+        let origin = (start.0 as f32, start.1 as f32);
+        let speed = speed as f32;
+        Self {
+            movement,
+            speed,
+            age: 0,
+            origin,
+            position: origin,
+            velocity: (-speed, 0.0),
+            progress: (0, 0.0),
+            arrived: None,
+        }
+    }
+
+    // move `base` one tick along the pattern
+    pub fn update(&mut self, base: &mut EntityBase, context: &UpdateContext) {
+        self.age += 1;
+        let age = self.age as f32;
+        let (origin_x, origin_y) = self.origin;
+        self.position = match &self.movement {
+            Movement::Straight => (origin_x - self.speed * age, origin_y),
+            Movement::Sine { amplitude, period } => (
+                origin_x - self.speed * age,
+                origin_y + amplitude * (TAU * age / *period as f32).sin(),
+            ),
+            Movement::Dive { steering } => {
+                let (x, y) = self.position;
+                let center = (x + base.width as f32 / 2.0, y + base.height as f32 / 2.0);
+                if let Some((player_x, player_y)) = context.player {
+                    if player_x as f32 <= center.0 {
+                        let (dx, dy) = (player_x as f32 - center.0, player_y as f32 - center.1);
+                        let distance = (dx * dx + dy * dy).sqrt().max(1.0);
+                        let desired = (dx / distance * self.speed, dy / distance * self.speed);
+                        self.velocity.0 += (desired.0 - self.velocity.0) * steering;
+                        self.velocity.1 += (desired.1 - self.velocity.1) * steering;
+                    }
+                }
+                (x + self.velocity.0, y + self.velocity.1)
+            }
+            Movement::StrafeHold { column, hold } => {
+                let (x, _) = self.position;
+                match self.arrived {
+                    None if x > *column as f32 => (x - self.speed, origin_y),
+                    None => {
+                        self.arrived = Some(self.age);
+                        (x, origin_y)
+                    }
+                    Some(arrived) => {
+                        let held = (self.age - arrived) as f32;
+                        let y = origin_y
+                            + Self::STRAFE_AMPLITUDE * (TAU * held / Self::STRAFE_PERIOD).sin();
+                        if self.age - arrived < *hold {
+                            (x, y)
+                        } else {
+                            (x - self.speed, y)
+                        }
+                    }
+                }
+            }
+            Movement::Circle { radius, period } => {
+                let angle = TAU * age / *period as f32;
+                let center = (origin_x - radius - self.speed * age, origin_y);
+                (
+                    center.0 + radius * angle.cos(),
+                    center.1 + radius * angle.sin(),
+                )
+            }
+            Movement::Path { points } => {
+                let (x, y) = self.position;
+                let point = |i: usize| {
+                    let (px, py) = points[i.min(points.len() - 1)];
+                    (origin_x + px as f32, origin_y + py as f32)
+                };
+                let (segment, t) = &mut self.progress;
+                if *segment + 1 < points.len() {
+                    // advance at roughly `speed` pixels per tick along the segment
+                    let (p1, p2) = (point(*segment), point(*segment + 1));
+                    let length = ((p2.0 - p1.0).powi(2) + (p2.1 - p1.1).powi(2))
+                        .sqrt()
+                        .max(1.0);
+                    *t += self.speed / length;
+                    if *t >= 1.0 {
+                        *t -= 1.0;
+                        *segment += 1;
+                    }
+                }
+                if *segment + 1 >= points.len() {
+                    (x - self.speed, y)
+                } else {
+                    catmull_rom(
+                        point(segment.saturating_sub(1)),
+                        point(*segment),
+                        point(*segment + 1),
+                        point(*segment + 2),
+                        *t,
+                    )
+                }
+            }
+        };
+        // ships only ever leave the screen sideways
+        let viewport = base.viewport;
+        let max_y = (viewport.y + viewport.h - base.height) as f32;
+        self.position.1 = self
+            .position
+            .1
+            .clamp(viewport.y as f32, max_y.max(viewport.y as f32));
+        base.x = self.position.0.round() as i32;
+        base.y = self.position.1.round() as i32;
+    }
+}
+
+// point at `t` on the Catmull-Rom spline segment between `p1` and `p2`
+fn catmull_rom(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    p3: (f32, f32),
+    t: f32,
+) -> (f32, f32) {
+    let (t2, t3) = (t * t, t * t * t);
+    let blend = |a: f32, b: f32, c: f32, d: f32| {
+        0.5 * (2.0 * b
+            + (c - a) * t
+            + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2
+            + (3.0 * b - a - 3.0 * c + d) * t3)
+    };
+    (blend(p0.0, p1.0, p2.0, p3.0), blend(p0.1, p1.1, p2.1, p3.1))
+}
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::EngineKind;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    // lives the player starts a run with
+    pub fn lives(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 1,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct Options {
+    // run the simulation for this many ticks without a visible window
+    pub headless_ticks: Option<u64>,
+    // seed for every random decision in the game, picked at random if absent
+    pub seed: Option<u64>,
+    // write every input with its frame number to this file
+    pub record: Option<String>,
+    // feed the inputs from this file into the game instead of the keyboard
+    pub replay: Option<String>,
+    pub engine: EngineKind,
+    // play the waves from this level file, random endless waves if absent
+    pub level: Option<String>,
+    pub difficulty: Difficulty,
+    // where high scores are kept
+    pub scores: String,
+}
+
+impl Options {
+    pub const USAGE: &'static str =
+        "usage: shoot [--headless <ticks>] [--seed <seed>] [--record <file>] [--replay <file>] \
+         [--engine base|big-pulse|burst|supercharged] [--level <file>] \
+         [--difficulty easy|normal|hard] [--scores <file>]";
+    const SCORES: &'static str = "highscores.ron";
+
+    pub fn from_args() -> Result<Self, String> {
+        let mut options = Self {
+            headless_ticks: None,
+            seed: None,
+            record: None,
+            replay: None,
+            engine: EngineKind::Base,
+            level: None,
+            difficulty: Difficulty::Normal,
+            scores: Self::SCORES.to_string(),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => options.headless_ticks = Some(Self::value(&arg, args.next())?),
+                "--seed" => options.seed = Some(Self::value(&arg, args.next())?),
+                "--record" => options.record = Some(Self::value(&arg, args.next())?),
+                "--replay" => options.replay = Some(Self::value(&arg, args.next())?),
+                "--engine" => options.engine = Self::value(&arg, args.next())?,
+                "--level" => options.level = Some(Self::value(&arg, args.next())?),
+                "--difficulty" => options.difficulty = Self::value(&arg, args.next())?,
+                "--scores" => options.scores = Self::value(&arg, args.next())?,
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        Ok(options)
+    }
+
+    fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+        let value = value.ok_or(format!("{flag} expects a value"))?;
+        value
+            .parse()
+            .map_err(|_| format!("invalid value `{value}` for {flag}"))
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless_ticks.is_some()
+    }
+}
@@ -1,84 +1,114 @@
-use sdl2::{event::Event, keyboard::Keycode, rect::Rect, render::WindowCanvas};
+use sdl2::{
+    event::Event,
+    keyboard::Keycode,
+    rect::Rect,
+    render::{TextureCreator, WindowCanvas},
+    video::WindowContext,
+};
 
 use crate::{
-    bullet::{Bullet, Side},
-    entity::{Entity, EntityBase, EntityEvent},
-    texture::ComponentTexture,
+    clock::Clock,
+    collision::{Contact, Hitbox, Layer, Shape},
+    engine::{Engine, EngineKind},
+    entity::{Entity, EntityBase, EntityEvent, UpdateContext},
+    shield::{Shield, ShieldKind},
+    texture::{ComponentTexture, BASE_TEXTURES, SHIELD_TEXTURES},
+    weapon::{Weapon, WeaponKind},
 };
 
+// equipment picked before a run
+#[derive(Clone, Copy)]
+pub struct Loadout {
+    pub engine: EngineKind,
+    pub weapon: WeaponKind,
+    pub shield: ShieldKind,
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Self {
+            engine: EngineKind::Base,
+            weapon: WeaponKind::AutoCannon,
+            shield: ShieldKind::Front,
+        }
+    }
+}
+
+// what the HUD shows of the player's ship
+pub struct Status {
+    pub hp: i32,
+    pub max_hp: i32,
+    pub lives: u32,
+    pub weapon: WeaponKind,
+}
+
 pub struct Player<'a> {
     base: EntityBase,
-    engine_texture: ComponentTexture<'a>,
-    engine_base_texture: ComponentTexture<'a>,
-    idle_texture: ComponentTexture<'a>,
-    body_texture: ComponentTexture<'a>,
-    projectile_texture: ComponentTexture<'a>,
-    weapon_texture: ComponentTexture<'a>,
-    pub firing_speed: usize,
-    pub cd: usize,
+    // one hull sprite per damage state, from full health to very damaged
+    body_textures: Vec<ComponentTexture<'a>>,
+    invincibility_texture: ComponentTexture<'a>,
+    pub engine: Engine<'a>,
+    pub shield: Shield<'a>,
+    pub weapon: Weapon<'a>,
+    pub hp: i32,
+    pub lives: u32,
+    // ticks left before the ship can be hit again
+    pub invincible_ticks: u64,
     pub firing: bool,
-    pub firing_ready: bool,
-    firing_left: bool,
+    dash: bool,
     pub left: bool,
     pub right: bool,
     pub up: bool,
     pub down: bool,
 }
 impl<'a> Entity<'a> for Player<'a> {
-    fn update(&mut self) -> EntityEvent<'a> {
-        if self.up {
-            self.base.update_y_rev();
+    fn update(&mut self, _context: &UpdateContext) -> Vec<EntityEvent<'a>> {
+        if self.invincible_ticks > 0 {
+            self.invincible_ticks -= 1;
+            self.invincibility_texture.tick();
         }
-        if self.down {
-            self.base.update_y();
-        }
-        if self.left {
-            self.base.update_x_rev();
-        }
-        if self.right {
-            self.base.update_x();
-        }
-        if self.firing {
-            if !self.firing_ready {
-                self.cd = (self.cd + 1) % self.firing_speed;
-                if self.cd == 0 {
-                    self.firing_ready = true;
-                }
-                EntityEvent::Empty
-            } else {
-                self.firing_ready = false;
-                self.firing_left = !self.firing_left;
-                let offset = if self.firing_left { 0 } else { 30 };
-                EntityEvent::SpawnBullet(self.spawn_bullet(offset))
-            }
-        } else {
-            EntityEvent::Empty
+        self.shield.update();
+        if self.dash {
+            self.engine.dash();
+            self.dash = false;
         }
+        let input = (
+            self.right as i32 - self.left as i32,
+            self.down as i32 - self.up as i32,
+        );
+        self.engine.update(&mut self.base, input);
+        let damage_state = self.damage_state();
+        self.body_textures[damage_state].tick();
+        self.weapon
+            .update(&self.base, self.firing)
+            .into_iter()
+            .map(EntityEvent::SpawnBullet)
+            .collect()
     }
 
-    fn render(&mut self, canvas: &mut WindowCanvas) {
-        self.body_texture
-            .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
-        self.engine_base_texture
-            .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
-        if !self.firing {
-            self.weapon_texture.render_nth(
-                0,
-                (self.base.x, self.base.y),
-                Self::DEFAULT_ANGLE,
-                canvas,
-            );
-        } else {
-            self.weapon_texture
-                .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
-        }
+    fn render(&mut self, canvas: &mut WindowCanvas, alpha: f64) {
+        let position = self.base.render_position(alpha);
+        let damage_state = self.damage_state();
+        self.body_textures[damage_state].render(position, Self::DEFAULT_ANGLE, canvas);
+        self.engine
+            .render_base(position, Self::DEFAULT_ANGLE, canvas);
+        self.weapon
+            .render(position, Self::DEFAULT_ANGLE, self.firing, canvas);
 
-        if self.is_moving() {
-            self.engine_texture
-                .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
+        let powering = self.is_moving();
+        self.engine
+            .render_effect(position, Self::DEFAULT_ANGLE, powering, canvas);
+
+        // shield sprites are larger than the hull, keep them centered on it
+        let center = (
+            position.0 + self.base.width / 2,
+            position.1 + self.base.height / 2,
+        );
+        if self.invincible_ticks > 0 {
+            self.invincibility_texture
+                .render_centered(center, Self::DEFAULT_ANGLE, canvas);
         } else {
-            self.idle_texture
-                .render((self.base.x, self.base.y), Self::DEFAULT_ANGLE, canvas);
+            self.shield.render(center, Self::DEFAULT_ANGLE, canvas);
         }
     }
 
@@ -100,8 +130,57 @@ impl<'a> Entity<'a> for Player<'a> {
         self.base.valid
     }
 
-    fn is_player(&self) -> bool {
-        true
+    fn on_collision(&mut self, other: &Contact) {
+        // pickups don't hurt, there's nothing to collect from them yet
+        if other.layer == Layer::PICKUP {
+            return;
+        }
+        // a short grace period after every hit, so a ship ramming into this
+        // one only counts once instead of on each tick they overlap
+        self.invincible_ticks = Clock::ticks_from_millis(Self::HIT_INVINCIBILITY);
+        let (x, y) = self.base.center();
+        let (source_x, source_y) = other.center;
+        if self
+            .shield
+            .absorb(other.damage, (source_x - x, source_y - y))
+        {
+            return;
+        }
+        self.hp = (self.hp - other.damage).max(0);
+        if self.hp > 0 {
+            return;
+        }
+        if self.lives > 1 {
+            self.lives -= 1;
+            self.respawn();
+        } else {
+            self.lives = 0;
+            self.base.valid = false;
+        }
+    }
+
+    fn is_invincible(&self) -> bool {
+        self.invincible_ticks > 0
+    }
+
+    fn status(&self) -> Option<Status> {
+        Some(Status {
+            hp: self.hp,
+            max_hp: Self::MAX_HP,
+            lives: self.lives,
+            weapon: self.weapon.kind,
+        })
+    }
+
+    fn hitbox(&self) -> Option<Shape> {
+        Some(Self::HITBOX.place(&self.base, Self::DEFAULT_ANGLE))
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::PLAYER
+    }
+    fn mask(&self) -> Layer {
+        Layer::ENEMY | Layer::ENEMY_PROJECTILE | Layer::PICKUP | Layer::HAZARD
     }
     fn base(&self) -> Option<&EntityBase> {
         Some(&self.base)
@@ -112,67 +191,69 @@ impl<'a> Entity<'a> for Player<'a> {
 }
 
 impl<'a> Player<'a> {
-    const DEFAULT_SPEED: i32 = 8;
     const DEFAULT_POSITION: (i32, i32) = (100, 100);
     const DEFAULT_ANGLE: f64 = 90.0;
+    const MAX_HP: i32 = 6;
+    const RESPAWN_INVINCIBILITY: u64 = 2_000;
+    const HIT_INVINCIBILITY: u64 = 500;
+    // only the cockpit can be hit, much smaller than the hull as usual for
+    // the genre so grazing shots pass by
+    const HITBOX: Hitbox = Hitbox::Circle { radius: 8.0 };
     pub fn new(
+        texture_creator: &'a TextureCreator<WindowContext>,
         viewport: Rect,
-        engine_texture: ComponentTexture<'a>,
-        engine_base_texture: ComponentTexture<'a>,
-        idle_texture: ComponentTexture<'a>,
-        weapon_texture: ComponentTexture<'a>,
-        body_texture: ComponentTexture<'a>,
-        projectile_texture: ComponentTexture<'a>,
+        loadout: Loadout,
+        lives: u32,
     ) -> Self {
-        let (width, height) = body_texture.size();
-        let base = EntityBase {
-            x: Self::DEFAULT_POSITION.0,
-            y: Self::DEFAULT_POSITION.1,
-            width: width.try_into().unwrap(),
-            height: height.try_into().unwrap(),
-            dx: Self::DEFAULT_SPEED,
-            dy: Self::DEFAULT_SPEED,
+        // hull sprites from full health to very damaged
+        let body_textures: Vec<_> = [1, 2, 0, 3]
+            .iter()
+            .map(|&i| ComponentTexture::new(texture_creator, &BASE_TEXTURES[i]))
+            .collect();
+        let invincibility_texture = ComponentTexture::new(texture_creator, &SHIELD_TEXTURES[3]);
+        let engine = Engine::new(texture_creator, loadout.engine);
+        let shield = Shield::new(texture_creator, loadout.shield);
+        let weapon = Weapon::new(texture_creator, loadout.weapon);
+        let base = EntityBase::new(
+            Self::DEFAULT_POSITION.0,
+            Self::DEFAULT_POSITION.1,
+            body_textures[0].size(),
+            0,
+            0,
             viewport,
-            valid: true,
-        };
+        );
         Self {
             base,
-            engine_texture,
-            engine_base_texture,
-            idle_texture,
-            body_texture,
-            weapon_texture,
-            projectile_texture,
-            firing_speed: 16,
-            cd: 0,
+            body_textures,
+            invincibility_texture,
+            engine,
+            shield,
+            weapon,
+            hp: Self::MAX_HP,
+            lives,
+            invincible_ticks: 0,
             firing: false,
-            firing_left: false,
-            firing_ready: true,
+            dash: false,
             left: false,
             right: false,
             up: false,
             down: false,
         }
     }
-    pub fn spawn_bullet(&self, offset: i32) -> Bullet<'a> {
-        let (width, height) = self.projectile_texture.size();
-        let base = EntityBase {
-            x: self.base.x,
-            y: self.base.y + offset,
-            width: width.try_into().unwrap(),
-            height: height.try_into().unwrap(),
-            dx: self.firing_speed as i32,
-            dy: 0,
-            viewport: self.base.viewport,
-            valid: true,
-        };
-
-        Bullet::new(
-            base,
-            Side::Player,
-            Self::DEFAULT_ANGLE,
-            self.projectile_texture.clone(),
-        )
+    // bring the ship back at its starting point after losing a life
+    fn respawn(&mut self) {
+        self.base.x = Self::DEFAULT_POSITION.0;
+        self.base.y = Self::DEFAULT_POSITION.1;
+        self.base.save_position();
+        self.engine.stop();
+        self.hp = Self::MAX_HP;
+        self.invincible_ticks = Clock::ticks_from_millis(Self::RESPAWN_INVINCIBILITY);
+    }
+
+    // index into `body_textures` for the current health
+    fn damage_state(&self) -> usize {
+        let states = self.body_textures.len() as i32;
+        ((Self::MAX_HP - self.hp) * states / Self::MAX_HP).clamp(0, states - 1) as usize
     }
 
     fn is_moving(&self) -> bool {
@@ -195,8 +276,16 @@ impl<'a> Player<'a> {
             }
             Keycode::LCtrl => {
                 self.firing = true;
-                self.firing_ready = true;
-                self.cd = 0;
+            }
+            Keycode::Num1 | Keycode::Num2 | Keycode::Num3 | Keycode::Num4 => {
+                let index = keycode as usize - Keycode::Num1 as usize;
+                self.weapon.select(WeaponKind::ALL[index]);
+            }
+            Keycode::S => {
+                self.shield.cycle();
+            }
+            Keycode::Space => {
+                self.dash = true;
             }
             _ => {}
         }
@@ -217,8 +306,6 @@ impl<'a> Player<'a> {
             }
             Keycode::LCtrl => {
                 self.firing = false;
-                self.firing_ready = true;
-                self.cd = 0;
             }
             _ => {}
         }
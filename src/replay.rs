@@ -0,0 +1,255 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use sdl2::{
+    event::Event,
+    keyboard::{Keycode, Mod},
+};
+
+use crate::{options::Difficulty, player::Loadout};
+
+// Replay files are plain text, one entry per line:
+//
+//     seed 1234
+//     engine base
+//     weapon auto-cannon
+//     shield front
+//     difficulty normal
+//     level levels/stage1.ron
+//     120 down Left
+//     121 repeat Left Ctrl
+//     150 up Left
+//     600 end
+//
+// The header holds everything the run was set up with, `level` is left out
+// for endless runs. Inputs are tagged with the frame they were fed into the
+// simulation, so together with the header the whole run can be reproduced
+// exactly.
+
+// how a run was set up
+pub struct Header {
+    pub seed: u64,
+    pub loadout: Loadout,
+    pub difficulty: Difficulty,
+    // path of the level file
+    pub level: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum KeyAction {
+    Down,
+    Repeat,
+    Up,
+}
+
+impl KeyAction {
+    fn name(&self) -> &'static str {
+        match self {
+            KeyAction::Down => "down",
+            KeyAction::Repeat => "repeat",
+            KeyAction::Up => "up",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "down" => Some(KeyAction::Down),
+            "repeat" => Some(KeyAction::Repeat),
+            "up" => Some(KeyAction::Up),
+            _ => None,
+        }
+    }
+}
+
+struct InputRecord {
+    frame: u64,
+    action: KeyAction,
+    keycode: Keycode,
+}
+
+impl InputRecord {
+    fn from_event(frame: u64, event: &Event) -> Option<Self> {
+        let (action, keycode) = match *event {
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat,
+                ..
+            } => (
+                if repeat {
+                    KeyAction::Repeat
+                } else {
+                    KeyAction::Down
+                },
+                keycode,
+            ),
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } => (KeyAction::Up, keycode),
+            _ => return None,
+        };
+        Some(Self {
+            frame,
+            action,
+            keycode,
+        })
+    }
+
+    fn to_event(&self) -> Event {
+        match self.action {
+            KeyAction::Down | KeyAction::Repeat => Event::KeyDown {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(self.keycode),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: self.action == KeyAction::Repeat,
+            },
+            KeyAction::Up => Event::KeyUp {
+                timestamp: 0,
+                window_id: 0,
+                keycode: Some(self.keycode),
+                scancode: None,
+                keymod: Mod::NOMOD,
+                repeat: false,
+            },
+        }
+    }
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &str, header: &Header) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("can't create `{path}`: {e}"))?;
+        let mut recorder = Self {
+            writer: BufWriter::new(file),
+        };
+        recorder.write_line(&format!("seed {}", header.seed));
+        recorder.write_line(&format!("engine {}", header.loadout.engine.id()));
+        recorder.write_line(&format!("weapon {}", header.loadout.weapon.id()));
+        recorder.write_line(&format!("shield {}", header.loadout.shield.id()));
+        recorder.write_line(&format!("difficulty {}", header.difficulty.name()));
+        if let Some(level) = &header.level {
+            recorder.write_line(&format!("level {level}"));
+        }
+        Ok(recorder)
+    }
+
+    // entries are flushed right away so a crash still leaves a usable replay
+    fn write_line(&mut self, line: &str) {
+        writeln!(self.writer, "{line}")
+            .and_then(|_| self.writer.flush())
+            .unwrap_or_else(|e| eprintln!("failed to write replay: {e}"));
+    }
+
+    pub fn record(&mut self, frame: u64, event: &Event) {
+        if let Some(input) = InputRecord::from_event(frame, event) {
+            self.write_line(&format!(
+                "{} {} {}",
+                input.frame,
+                input.action.name(),
+                input.keycode.name()
+            ));
+        }
+    }
+
+    pub fn finish(&mut self, frame: u64) {
+        self.write_line(&format!("{frame} end"));
+    }
+}
+
+pub struct Replay {
+    pub header: Header,
+    end_frame: Option<u64>,
+    inputs: VecDeque<InputRecord>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("can't read `{path}`: {e}"))?;
+        let mut lines = content.lines().enumerate().peekable();
+        let invalid = |line: usize| format!("{path}:{}: invalid replay entry", line + 1);
+
+        let seed = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix("seed "))
+            .and_then(|seed| seed.parse().ok())
+            .ok_or_else(|| invalid(0))?;
+        // settings missing from the header keep their defaults
+        let mut header = Header {
+            seed,
+            loadout: Loadout::default(),
+            difficulty: Difficulty::Normal,
+            level: None,
+        };
+        // the header ends with the first input, those start with a frame number
+        while let Some((index, line)) =
+            lines.next_if(|(_, line)| !line.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let (key, value) = line.split_once(' ').ok_or_else(|| invalid(index))?;
+            match key {
+                "engine" => header.loadout.engine = value.parse().map_err(|_| invalid(index))?,
+                "weapon" => header.loadout.weapon = value.parse().map_err(|_| invalid(index))?,
+                "shield" => header.loadout.shield = value.parse().map_err(|_| invalid(index))?,
+                "difficulty" => header.difficulty = value.parse().map_err(|_| invalid(index))?,
+                "level" => header.level = Some(value.to_string()),
+                _ => return Err(invalid(index)),
+            }
+        }
+        let mut replay = Self {
+            header,
+            end_frame: None,
+            inputs: VecDeque::new(),
+        };
+        for (index, line) in lines {
+            let mut parts = line.splitn(3, ' ');
+            let frame = parts
+                .next()
+                .and_then(|frame| frame.parse().ok())
+                .ok_or_else(|| invalid(index))?;
+            let action = parts.next().ok_or_else(|| invalid(index))?;
+            if action == "end" {
+                replay.end_frame = Some(frame);
+                break;
+            }
+            let action = KeyAction::from_name(action).ok_or_else(|| invalid(index))?;
+            let keycode = parts
+                .next()
+                .and_then(Keycode::from_name)
+                .ok_or_else(|| invalid(index))?;
+            replay.inputs.push_back(InputRecord {
+                frame,
+                action,
+                keycode,
+            });
+        }
+        Ok(replay)
+    }
+
+    // the inputs that were fed into the simulation on `frame`
+    pub fn take_events(&mut self, frame: u64) -> Vec<Event> {
+        let mut events = vec![];
+        while let Some(input) = self.inputs.front() {
+            if input.frame > frame {
+                break;
+            }
+            events.push(input.to_event());
+            self.inputs.pop_front();
+        }
+        events
+    }
+
+    pub fn is_finished(&self, frame: u64) -> bool {
+        match self.end_frame {
+            Some(end_frame) => frame >= end_frame,
+            None => false,
+        }
+    }
+}
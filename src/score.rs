@@ -0,0 +1,50 @@
+use crate::clock::Clock;
+
+// Points for shooting ships down. Kills in quick succession build up a
+// chain that raises the multiplier, it drops back a step whenever the chain
+// stalls for too long.
+#[derive(Default)]
+pub struct Score {
+    pub points: u64,
+    // kills counted towards the multiplier
+    chain: u32,
+    // simulated time in milliseconds of the last kill or multiplier drop
+    last_change: u64,
+}
+
+impl Score {
+    // kills for each step up the multiplier
+    const KILLS_PER_STEP: u32 = 5;
+    const MAX_MULTIPLIER: u32 = 8;
+    // milliseconds without a kill before the multiplier drops a step
+    const DECAY_TIME: u64 = 3_000;
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.chain / Self::KILLS_PER_STEP).min(Self::MAX_MULTIPLIER)
+    }
+
+    pub fn kill(&mut self, points: u32, clock: &Clock) {
+        self.points += points as u64 * self.multiplier() as u64;
+        self.chain = (self.chain + 1).min((Self::MAX_MULTIPLIER - 1) * Self::KILLS_PER_STEP);
+        self.last_change = clock.millis();
+    }
+
+    // called once per tick
+    pub fn update(&mut self, clock: &Clock) {
+        if self.chain > 0 && clock.millis() - self.last_change >= Self::DECAY_TIME {
+            // back to the start of the step below
+            self.chain = (self.multiplier() - 1).saturating_sub(1) * Self::KILLS_PER_STEP;
+            self.last_change = clock.millis();
+        }
+    }
+
+    // share of the decay time left before the multiplier drops, 0 when
+    // there's no chain going
+    pub fn chain_left(&self, clock: &Clock) -> f32 {
+        if self.chain == 0 {
+            return 0.0;
+        }
+        let elapsed = clock.millis() - self.last_change;
+        1.0 - elapsed.min(Self::DECAY_TIME) as f32 / Self::DECAY_TIME as f32
+    }
+}
@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use sdl2::{
+    render::{TextureCreator, WindowCanvas},
+    video::WindowContext,
+};
+
+use crate::texture::{ComponentTexture, SHIELD_TEXTURES};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ShieldKind {
+    Front,
+    FrontAndSide,
+    Round,
+}
+
+impl ShieldKind {
+    pub const ALL: [ShieldKind; 3] = [
+        ShieldKind::Front,
+        ShieldKind::FrontAndSide,
+        ShieldKind::Round,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShieldKind::Front => "Front",
+            ShieldKind::FrontAndSide => "Front and Side",
+            ShieldKind::Round => "Round",
+        }
+    }
+
+    // how it's spelled in replay files
+    pub fn id(&self) -> &'static str {
+        match self {
+            ShieldKind::Front => "front",
+            ShieldKind::FrontAndSide => "front-and-side",
+            ShieldKind::Round => "round",
+        }
+    }
+
+    // whether a hit coming from (dx, dy) relative to the ship's center is
+    // covered, the ship faces towards positive x
+    fn covers(&self, dx: i32, dy: i32) -> bool {
+        match self {
+            ShieldKind::Front => dx > dy.abs(),
+            ShieldKind::FrontAndSide => -dx <= dy.abs(),
+            ShieldKind::Round => true,
+        }
+    }
+
+    // energy spent per point of damage absorbed, wider coverage costs more
+    fn cost(&self) -> i32 {
+        match self {
+            ShieldKind::Front => 100,
+            ShieldKind::FrontAndSide => 150,
+            ShieldKind::Round => 200,
+        }
+    }
+
+    fn index(&self) -> usize {
+        ShieldKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap()
+    }
+}
+
+impl FromStr for ShieldKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "front" => Ok(ShieldKind::Front),
+            "front-and-side" => Ok(ShieldKind::FrontAndSide),
+            "round" => Ok(ShieldKind::Round),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct Shield<'a> {
+    pub kind: ShieldKind,
+    pub energy: i32,
+    // ticks left before the shield starts recharging
+    recharge_delay: u64,
+    // one texture per kind, in the order of `ShieldKind::ALL`
+    textures: Vec<ComponentTexture<'a>>,
+}
+
+impl<'a> Shield<'a> {
+    pub const MAX_ENERGY: i32 = 600;
+    const RECHARGE_RATE: i32 = 1;
+    const RECHARGE_DELAY: u64 = 60;
+
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, kind: ShieldKind) -> Self {
+        let textures = ShieldKind::ALL
+            .iter()
+            .map(|kind| ComponentTexture::new(texture_creator, &SHIELD_TEXTURES[kind.index()]))
+            .collect();
+        Self {
+            kind,
+            energy: Self::MAX_ENERGY,
+            recharge_delay: 0,
+            textures,
+        }
+    }
+
+    pub fn cycle(&mut self) {
+        let next = (self.kind.index() + 1) % ShieldKind::ALL.len();
+        self.kind = ShieldKind::ALL[next];
+    }
+
+    // a shield only comes up with enough energy to take a full hit
+    pub fn is_up(&self) -> bool {
+        self.energy >= self.kind.cost()
+    }
+
+    // try to soak up a hit coming from (dx, dy), returns whether it was absorbed
+    pub fn absorb(&mut self, damage: i32, (dx, dy): (i32, i32)) -> bool {
+        if !self.is_up() || !self.kind.covers(dx, dy) {
+            return false;
+        }
+        self.energy = (self.energy - damage * self.kind.cost()).max(0);
+        self.recharge_delay = Self::RECHARGE_DELAY;
+        true
+    }
+
+    pub fn update(&mut self) {
+        if self.recharge_delay > 0 {
+            self.recharge_delay -= 1;
+        } else {
+            self.energy = (self.energy + Self::RECHARGE_RATE).min(Self::MAX_ENERGY);
+        }
+        self.textures[self.kind.index()].tick();
+    }
+
+    pub fn render(&mut self, center: (i32, i32), angle: f64, canvas: &mut WindowCanvas) {
+        if self.is_up() {
+            self.textures[self.kind.index()].render_centered(center, angle, canvas);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// Where the game is at, each state takes its own input and draws its own
+// screen:
+//
+//     Title -> Loadout -> Playing <-> Paused
+//                            |
+//                            v
+//                         GameOver -> Results -> Title
+//
+// Leaving a run early from Playing or Paused goes back to the title.
+pub enum State {
+    Title,
+    // picking equipment and difficulty, `row` is the highlighted line
+    Loadout { row: usize },
+    Playing,
+    Paused,
+    // the player's ship is down, the world plays on until tick `until`
+    GameOver { until: u64 },
+    Results(Results),
+}
+
+pub enum Results {
+    // typing in a name for a new high score
+    NameEntry(String),
+    // the high scores for the run's conditions, with the rank of the run's
+    // own entry if it made it in
+    Leaderboard(Option<usize>),
+}
+
+// lines of the loadout screen
+pub const LOADOUT_ROWS: [&str; 4] = ["ENGINE", "WEAPON", "SHIELD", "DIFFICULTY"];
+
+// the item `step` places after `current` in `all`, wrapping around
+pub fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: isize) -> T {
+    let index = all.iter().position(|&item| item == current).unwrap_or(0);
+    all[(index as isize + step).rem_euclid(all.len() as isize) as usize]
+}
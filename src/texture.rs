@@ -1,40 +1,136 @@
-use std::rc::Rc;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use sdl2::{
-    image::LoadTexture,
-    pixels::Color,
+    image::{LoadSurface, LoadTexture},
+    pixels::PixelFormatEnum,
     rect::Rect,
     render::{Texture, TextureCreator, WindowCanvas},
+    surface::Surface,
     video::WindowContext,
 };
 
+use crate::collision::Hitbox;
+
+// draw hitboxes over the sprites
 pub const RENDER_DEBUG: bool = false;
+// sprites are drawn this many times their size in the image files
+pub const SCALE: u32 = 2;
+// pixels at least this opaque count as part of a sprite's shape
+const OPAQUE_ALPHA: u8 = 128;
 
 pub struct TextureInfo<'a> {
     pub path: &'a str,
     pub total_frame: usize,
 }
 
+// Loads each file once and hands out textures sharing it, for entities
+// that are created over and over during a run
+pub struct TextureCache<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
+    textures: RefCell<HashMap<&'static str, Rc<Texture<'a>>>>,
+    hitboxes: RefCell<HashMap<&'static str, Hitbox>>,
+}
+
+impl<'a> TextureCache<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+        Self {
+            texture_creator,
+            textures: RefCell::new(HashMap::new()),
+            hitboxes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, info: &TextureInfo<'static>) -> ComponentTexture<'a> {
+        let texture = self
+            .textures
+            .borrow_mut()
+            .entry(info.path)
+            .or_insert_with(|| Rc::new(self.texture_creator.load_texture(info.path).unwrap()))
+            .clone();
+        ComponentTexture::from_texture(texture, info.total_frame)
+    }
+
+    // hitbox traced around the opaque pixels of the sprite with `trace`,
+    // done once per file
+    pub fn hitbox(
+        &self,
+        info: &TextureInfo<'static>,
+        trace: fn(&[(i32, i32)]) -> Hitbox,
+    ) -> Hitbox {
+        self.hitboxes
+            .borrow_mut()
+            .entry(info.path)
+            .or_insert_with(|| trace(&opaque_outline(info).unwrap()))
+            .clone()
+    }
+}
+
+// corners of the outermost opaque pixels on each row of a sprite, in the
+// pixels of one frame and taken over all of its frames
+pub fn opaque_outline(info: &TextureInfo) -> Result<Vec<(i32, i32)>, String> {
+    let surface = Surface::from_file(info.path)?.convert_format(PixelFormatEnum::RGBA32)?;
+    let (width, height) = (surface.width() as usize, surface.height() as usize);
+    let frame_width = width / info.total_frame;
+    let pitch = surface.pitch() as usize;
+    Ok(surface.with_lock(|pixels| {
+        let mut points = vec![];
+        for y in 0..height {
+            let row = &pixels[y * pitch..];
+            let opaque = (0..width)
+                .filter(|x| row[x * 4 + 3] >= OPAQUE_ALPHA)
+                .map(|x| (x % frame_width) as i32);
+            if let (Some(left), Some(right)) = (opaque.clone().min(), opaque.max()) {
+                let y = y as i32;
+                points.extend([(left, y), (left, y + 1), (right + 1, y), (right + 1, y + 1)]);
+            }
+        }
+        points
+    }))
+}
+
 #[derive(Clone)]
 pub struct ComponentTexture<'a> {
     pub texture: Rc<Texture<'a>>,
     pub total_frame: usize,
     pub cnt: usize,
     pub current_frame: usize,
+    // a non-looping animation stops on its last frame
+    pub looping: bool,
 }
 
 impl<'a> ComponentTexture<'a> {
     const SPEED: usize = 5;
     pub fn new(texture_creator: &'a TextureCreator<WindowContext>, texture: &TextureInfo) -> Self {
         let t = texture_creator.load_texture(texture.path).unwrap();
+        Self::from_texture(Rc::new(t), texture.total_frame)
+    }
+
+    fn from_texture(texture: Rc<Texture<'a>>, total_frame: usize) -> Self {
         ComponentTexture {
-            texture: Rc::new(t),
-            total_frame: texture.total_frame,
+            texture,
+            total_frame,
             cnt: 0,
             current_frame: 0,
+            looping: true,
         }
     }
 
+    // play the animation a single time instead of looping it
+    pub fn play_once(mut self) -> Self {
+        self.looping = false;
+        self
+    }
+
+    pub fn restart(&mut self) {
+        self.cnt = 0;
+        self.current_frame = 0;
+    }
+
+    // whether a non-looping animation has played through
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.cnt >= self.total_frame * Self::SPEED
+    }
+
     // return width and height
     pub fn size(&self) -> (u32, u32) {
         let query = self.texture.query();
@@ -53,8 +149,12 @@ impl<'a> ComponentTexture<'a> {
     ) {
         let (width, height) = self.size();
         let src_rect = Rect::new(index as i32 * width as i32, 0, width, height);
-        let (x, y) = (offset.0 - width as i32 / 2, offset.1 - height as i32 / 2);
-        let dest_rect = Rect::new(x, y, width * 2, height * 2);
+        // scaled up around the middle of the unscaled sprite at `offset`
+        let (x, y) = (
+            offset.0 + (width / 2) as i32 - (width * SCALE / 2) as i32,
+            offset.1 + (height / 2) as i32 - (height * SCALE / 2) as i32,
+        );
+        let dest_rect = Rect::new(x, y, width * SCALE, height * SCALE);
         canvas
             .copy_ex(
                 &self.texture,
@@ -66,20 +166,25 @@ impl<'a> ComponentTexture<'a> {
                 false,
             )
             .ok();
-        let color = canvas.draw_color();
-        if RENDER_DEBUG {
-            canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
-            canvas.set_draw_color(Color::RGBA(100, 0, 0, 20));
-            canvas
-                .fill_rect(Rect::new(offset.0, offset.1, width, height))
-                .ok();
-            canvas.set_draw_color(color);
-        }
     }
     pub fn render(&mut self, offset: (i32, i32), angle: f64, canvas: &mut WindowCanvas) {
         self.render_nth(self.current_frame, offset, angle, canvas);
-        self.cnt = (self.cnt + 1) % (self.total_frame * Self::SPEED);
-        self.current_frame = self.cnt / Self::SPEED;
+    }
+    // render with the sprite centered on `center` instead of anchored at an offset
+    pub fn render_centered(&mut self, center: (i32, i32), angle: f64, canvas: &mut WindowCanvas) {
+        let (width, height) = self.size();
+        let offset = (center.0 - width as i32 / 2, center.1 - height as i32 / 2);
+        self.render(offset, angle, canvas);
+    }
+    // advance the animation, called once per simulation tick
+    pub fn tick(&mut self) {
+        let length = self.total_frame * Self::SPEED;
+        if self.looping {
+            self.cnt = (self.cnt + 1) % length;
+        } else {
+            self.cnt = (self.cnt + 1).min(length);
+        }
+        self.current_frame = (self.cnt / Self::SPEED).min(self.total_frame - 1);
     }
 }
 
@@ -126,15 +231,15 @@ pub const ENGINE_TEXTURES: [TextureInfo<'static>; 4] = [
         total_frame: 1,
     },
     TextureInfo {
-        path: "assets/Main Ship/Main Ship - Engines/PNGs/Main Ship - Engines - Big Pulse Engine - Powering.png",
+        path: "assets/Main Ship/Main Ship - Engines/PNGs/Main Ship - Engines - Big Pulse Engine.png",
         total_frame: 1,
     },
     TextureInfo {
-        path: "assets/Main Ship/Main Ship - Engines/PNGs/Main Ship - Engines - Burst Engine - Powering.png",
+        path: "assets/Main Ship/Main Ship - Engines/PNGs/Main Ship - Engines - Burst Engine.png",
         total_frame: 1,
     },
     TextureInfo {
-        path: "assets/Main Ship/Main Ship - Engines/PNGs/Main Ship - Engines - Supercharged Engine - Powering.png",
+        path: "assets/Main Ship/Main Ship - Engines/PNGs/Main Ship - Engines - Supercharged Engine.png",
         total_frame: 1,
     },
 ];
@@ -192,35 +297,209 @@ pub const BASE_TEXTURES: [TextureInfo<'static>; 4] = [
         total_frame: 1,
     },
 ];
-
-pub const ENEMY_BASE_TEXTURES: [TextureInfo<'static>; 2] = [
+pub const SHIELD_TEXTURES: [TextureInfo<'static>; 4] = [
     TextureInfo {
-        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Scout - Weapons.png",
-        total_frame: 6,
+        path: "assets/Main Ship/Main Ship - Shields/PNGs/Main Ship - Shields - Front Shield.png",
+        total_frame: 10,
     },
     TextureInfo {
-        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Frigate - Weapons.png",
+        path: "assets/Main Ship/Main Ship - Shields/PNGs/Main Ship - Shields - Front and Side Shield.png",
         total_frame: 6,
     },
-];
-pub const ENEMY_PROJECTILE_TEXTURES: [TextureInfo<'static>; 2] = [
     TextureInfo {
-        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Bullet.png",
-        total_frame: 4,
+        path: "assets/Main Ship/Main Ship - Shields/PNGs/Main Ship - Shields - Round Shield.png",
+        total_frame: 12,
     },
     TextureInfo {
-        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Big Bullet.png",
-        total_frame: 4,
+        path: "assets/Main Ship/Main Ship - Shields/PNGs/Main Ship - Shields - Invincibility Shield.png",
+        total_frame: 10,
     },
 ];
 
-pub const ENEMY_ENGINE_TEXTURES: [TextureInfo<'static>; 2] = [
+// Kla'ed textures are indexed in the order of `EnemyKind::ALL`
+pub const ENEMY_BASE_TEXTURES: [TextureInfo<'static>; 8] = [
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Scout - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Fighter - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Frigate - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Bomber - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Torpedo Ship - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Support ship - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Battlecruiser - Base.png",
+        total_frame: 1,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Base/PNGs/Kla'ed - Dreadnought - Base.png",
+        total_frame: 1,
+    },
+];
+pub const ENEMY_ENGINE_TEXTURES: [TextureInfo<'static>; 8] = [
     TextureInfo {
         path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Scout - Engine.png",
         total_frame: 10,
     },
+    TextureInfo {
+        path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Fighter - Engine.png",
+        total_frame: 10,
+    },
     TextureInfo {
         path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Frigate - Engine.png",
         total_frame: 12,
     },
+    TextureInfo {
+        path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Bomber - Engine.png",
+        total_frame: 10,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Torpedo Ship - Engine.png",
+        total_frame: 10,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Support ship - Engine.png",
+        total_frame: 10,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Battlecruiser - Engine.png",
+        total_frame: 12,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Engine/PNGs/Kla'ed - Dreadnought - Engine.png",
+        total_frame: 12,
+    },
+];
+pub const ENEMY_DESTRUCTION_TEXTURES: [TextureInfo<'static>; 8] = [
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Scout - Destruction.png",
+        total_frame: 10,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Fighter - Destruction.png",
+        total_frame: 9,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Frigate - Destruction.png",
+        total_frame: 9,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Bomber - Destruction.png",
+        total_frame: 8,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Torpedo Ship - Destruction.png",
+        total_frame: 10,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Support ship - Destruction.png",
+        total_frame: 10,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Battlecruiser - Destruction.png",
+        total_frame: 14,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Destruction/PNGs/Kla'ed - Dreadnought - Destruction.png",
+        total_frame: 12,
+    },
+];
+// the Support ship has no shield sprite
+pub const ENEMY_SHIELD_TEXTURES: [Option<TextureInfo<'static>>; 8] = [
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Scout - Shield.png",
+        total_frame: 14,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Fighter - Shield.png",
+        total_frame: 10,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Frigate - Shield.png",
+        total_frame: 40,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Bomber - Shield.png",
+        total_frame: 6,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Torpedo Ship - Shield.png",
+        total_frame: 10,
+    }),
+    None,
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Battlecruiser - Shield.png",
+        total_frame: 16,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Shield/PNGs/Kla'ed - Dreadnought - Shield.png",
+        total_frame: 10,
+    }),
+];
+// the Bomber and Support ship have no weapon sprites
+pub const ENEMY_WEAPON_TEXTURES: [Option<TextureInfo<'static>>; 8] = [
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Scout - Weapons.png",
+        total_frame: 6,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Fighter - Weapons.png",
+        total_frame: 6,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Frigate - Weapons.png",
+        total_frame: 6,
+    }),
+    None,
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Torpedo Ship - Weapons.png",
+        total_frame: 16,
+    }),
+    None,
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Battlecruiser - Weapons.png",
+        total_frame: 30,
+    }),
+    Some(TextureInfo {
+        path: "assets/Kla'ed/Weapons/PNGs/Kla'ed - Dreadnought - Weapons.png",
+        total_frame: 60,
+    }),
+];
+// indexed in the order of `ProjectileKind::ALL`
+pub const ENEMY_PROJECTILE_TEXTURES: [TextureInfo<'static>; 5] = [
+    TextureInfo {
+        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Bullet.png",
+        total_frame: 4,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Big Bullet.png",
+        total_frame: 4,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Wave.png",
+        total_frame: 6,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Ray.png",
+        total_frame: 4,
+    },
+    TextureInfo {
+        path: "assets/Kla'ed/Projectiles/PNGs/Kla'ed - Torpedo.png",
+        total_frame: 3,
+    },
 ];
@@ -0,0 +1,231 @@
+use std::str::FromStr;
+
+use sdl2::{
+    render::{TextureCreator, WindowCanvas},
+    video::WindowContext,
+};
+
+use crate::{
+    bullet::{Bullet, Side},
+    collision::Hitbox,
+    entity::EntityBase,
+    texture::{opaque_outline, ComponentTexture, PROJECTILE_TEXTURES, WEAPON_TEXTURES},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WeaponKind {
+    AutoCannon,
+    BigSpaceGun,
+    Rockets,
+    Zapper,
+}
+
+// how the projectiles of one shot are laid out
+#[derive(Clone, Copy)]
+pub enum FirePattern {
+    // one projectile per shot, alternating between two vertical offsets
+    Alternating(i32, i32),
+    // `count` projectiles fanning out, `step` apart in vertical speed
+    Spread { count: i32, step: i32 },
+    // two projectiles side by side, `gap` pixels apart
+    Pair { gap: i32 },
+    // one projectile from the middle of the ship
+    Stream,
+}
+
+pub struct WeaponStats {
+    // ticks between two shots
+    pub fire_interval: usize,
+    pub damage: i32,
+    // projectile speed in pixels per tick
+    pub speed: i32,
+    pub pattern: FirePattern,
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 4] = [
+        WeaponKind::AutoCannon,
+        WeaponKind::BigSpaceGun,
+        WeaponKind::Rockets,
+        WeaponKind::Zapper,
+    ];
+
+    pub fn stats(&self) -> WeaponStats {
+        match self {
+            WeaponKind::AutoCannon => WeaponStats {
+                fire_interval: 16,
+                damage: 1,
+                speed: 16,
+                pattern: FirePattern::Alternating(0, 30),
+            },
+            WeaponKind::BigSpaceGun => WeaponStats {
+                fire_interval: 45,
+                damage: 4,
+                speed: 22,
+                pattern: FirePattern::Spread { count: 3, step: 2 },
+            },
+            WeaponKind::Rockets => WeaponStats {
+                fire_interval: 36,
+                damage: 3,
+                speed: 7,
+                pattern: FirePattern::Pair { gap: 24 },
+            },
+            WeaponKind::Zapper => WeaponStats {
+                fire_interval: 5,
+                damage: 1,
+                speed: 20,
+                pattern: FirePattern::Stream,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::AutoCannon => "Auto Cannon",
+            WeaponKind::BigSpaceGun => "Big Space Gun",
+            WeaponKind::Rockets => "Rockets",
+            WeaponKind::Zapper => "Zapper",
+        }
+    }
+
+    // how it's spelled in replay files
+    pub fn id(&self) -> &'static str {
+        match self {
+            WeaponKind::AutoCannon => "auto-cannon",
+            WeaponKind::BigSpaceGun => "big-space-gun",
+            WeaponKind::Rockets => "rockets",
+            WeaponKind::Zapper => "zapper",
+        }
+    }
+
+    fn index(&self) -> usize {
+        WeaponKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap()
+    }
+}
+
+impl FromStr for WeaponKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "auto-cannon" => Ok(WeaponKind::AutoCannon),
+            "big-space-gun" => Ok(WeaponKind::BigSpaceGun),
+            "rockets" => Ok(WeaponKind::Rockets),
+            "zapper" => Ok(WeaponKind::Zapper),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct Weapon<'a> {
+    pub kind: WeaponKind,
+    // weapon mount and projectile sprites, in the order of `WeaponKind::ALL`
+    textures: Vec<ComponentTexture<'a>>,
+    projectile_textures: Vec<ComponentTexture<'a>>,
+    projectile_hitboxes: Vec<Hitbox>,
+    // ticks left before the next shot
+    cooldown: usize,
+    fire_left: bool,
+}
+
+impl<'a> Weapon<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, kind: WeaponKind) -> Self {
+        let load = |infos: &[_]| {
+            WeaponKind::ALL
+                .iter()
+                .map(|kind| ComponentTexture::new(texture_creator, &infos[kind.index()]))
+                .collect()
+        };
+        Self {
+            kind,
+            textures: load(&WEAPON_TEXTURES),
+            projectile_textures: load(&PROJECTILE_TEXTURES),
+            projectile_hitboxes: WeaponKind::ALL
+                .iter()
+                .map(|kind| {
+                    Hitbox::bounding(&opaque_outline(&PROJECTILE_TEXTURES[kind.index()]).unwrap())
+                })
+                .collect(),
+            cooldown: 0,
+            fire_left: false,
+        }
+    }
+
+    // the cooldown carries over, swapping back and forth doesn't fire faster
+    pub fn select(&mut self, kind: WeaponKind) {
+        self.kind = kind;
+    }
+
+    // called every tick, the cooldown runs down whether or not the trigger
+    // is held so tapping it can't fire faster than `fire_interval`, returns
+    // the projectiles fired
+    pub fn update(&mut self, base: &EntityBase, firing: bool) -> Vec<Bullet<'a>> {
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+        }
+        if !firing {
+            return vec![];
+        }
+        self.textures[self.kind.index()].tick();
+        if self.cooldown > 0 {
+            return vec![];
+        }
+        let stats = self.kind.stats();
+        self.cooldown = stats.fire_interval;
+
+        let projectile_texture = &self.projectile_textures[self.kind.index()];
+        let (_, height) = projectile_texture.size();
+        let center = (base.height - height as i32) / 2;
+        // vertical offset and speed of each projectile
+        let shots = match stats.pattern {
+            FirePattern::Alternating(left, right) => {
+                self.fire_left = !self.fire_left;
+                vec![(if self.fire_left { left } else { right }, 0)]
+            }
+            FirePattern::Spread { count, step } => (0..count)
+                .map(|i| (center, (i - (count - 1) / 2) * step))
+                .collect(),
+            FirePattern::Pair { gap } => vec![(center - gap / 2, 0), (center + gap / 2, 0)],
+            FirePattern::Stream => vec![(center, 0)],
+        };
+        shots
+            .into_iter()
+            .map(|(offset, dy)| {
+                let bullet_base = EntityBase::new(
+                    base.x,
+                    base.y + offset,
+                    projectile_texture.size(),
+                    stats.speed,
+                    dy,
+                    base.viewport,
+                );
+                Bullet::new(
+                    bullet_base,
+                    Side::Player,
+                    (stats.speed as f32, dy as f32),
+                    stats.damage,
+                    self.projectile_hitboxes[self.kind.index()].clone(),
+                    projectile_texture.clone(),
+                )
+            })
+            .collect()
+    }
+
+    pub fn render(
+        &mut self,
+        position: (i32, i32),
+        angle: f64,
+        firing: bool,
+        canvas: &mut WindowCanvas,
+    ) {
+        let texture = &mut self.textures[self.kind.index()];
+        if firing {
+            texture.render(position, angle, canvas);
+        } else {
+            texture.render_nth(0, position, angle, canvas);
+        }
+    }
+}